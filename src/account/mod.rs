@@ -1,10 +1,11 @@
+use crate::money::Money;
 use std::fs::File;
 use std::io::{self, BufRead, Write};
 
 #[derive(Clone)]
 pub struct Account {
     pub username: String,
-    pub money: u32,
+    pub money: Money,
     pub pickaxe_level: u8,
 }
 
@@ -12,7 +13,7 @@ impl Account {
     pub fn new_account(username: String) -> Account {
         Account {
             username,
-            money: 100,
+            money: Money::new(100),
             pickaxe_level: 1,
         }
     }
@@ -25,7 +26,7 @@ pub fn parse_account_string(account_string: &str) -> Result<Account, &'static st
     }
 
     let username = tokens[0].to_string();
-    let money = tokens[1].trim().parse().unwrap_or(0);
+    let money = tokens[1].trim().parse().unwrap_or_default();
     let pickaxe_level = tokens[2].trim().parse().unwrap_or(1);
 
     Ok(Account {
@@ -40,18 +41,16 @@ pub fn parse_account_file(file_path: &str) -> Result<Vec<Account>, io::Error> {
     let reader = io::BufReader::new(file);
     let mut accounts = Vec::new();
 
-    for line in reader.lines() {
-        if let Ok(line_content) = line {
-            if let Ok(account) = parse_account_string(&line_content) {
-                accounts.push(account);
-            }
+    for line_content in reader.lines().map_while(Result::ok) {
+        if let Ok(account) = parse_account_string(&line_content) {
+            accounts.push(account);
         }
     }
 
     Ok(accounts)
 }
 
-pub fn update_account_file(accounts: &Vec<Account>, file_path: &str) -> io::Result<()> {
+pub fn update_account_file(accounts: &[Account], file_path: &str) -> io::Result<()> {
     let mut file = File::create(file_path)?;
 
     for account in accounts {
@@ -0,0 +1,235 @@
+use crate::event::GameEvent;
+use crate::inventory::Ore;
+use crate::lang::{text, tr};
+use crate::player::Player;
+use crate::timestamp::{format_time, now};
+use std::fs::File;
+use std::io::{self, BufRead, Write};
+
+pub enum Goal {
+    MineDiamonds(u32),
+    ReachDepth(u32),
+    PickaxeLevel(u32),
+    SurviveCreepers(u32),
+    EarnMoney(u32),
+    EatFood(u32),
+}
+
+pub struct Achievement {
+    pub id: &'static str,
+    // catalog keys, see the lang module
+    pub name: &'static str,
+    pub description: &'static str,
+    pub goal: Goal,
+}
+
+pub const CATALOG: [Achievement; 6] = [
+    Achievement {
+        id: "first_diamond",
+        name: "achievement.first_diamond.name",
+        description: "achievement.first_diamond.description",
+        goal: Goal::MineDiamonds(1),
+    },
+    Achievement {
+        id: "depth_20",
+        name: "achievement.depth_20.name",
+        description: "achievement.depth_20.description",
+        goal: Goal::ReachDepth(20),
+    },
+    Achievement {
+        id: "max_pickaxe",
+        name: "achievement.max_pickaxe.name",
+        description: "achievement.max_pickaxe.description",
+        goal: Goal::PickaxeLevel(3),
+    },
+    Achievement {
+        id: "creeper_survivor",
+        name: "achievement.creeper_survivor.name",
+        description: "achievement.creeper_survivor.description",
+        goal: Goal::SurviveCreepers(1),
+    },
+    Achievement {
+        id: "earn_10000",
+        name: "achievement.earn_10000.name",
+        description: "achievement.earn_10000.description",
+        goal: Goal::EarnMoney(10000),
+    },
+    Achievement {
+        id: "eat_50",
+        name: "achievement.eat_50.name",
+        description: "achievement.eat_50.description",
+        goal: Goal::EatFood(50),
+    },
+];
+
+// Per account counters and unlocked achievements
+pub struct Achievements {
+    pub diamonds_mined: u32,
+    pub max_depth: u32,
+    pub pickaxe_level: u32,
+    pub creepers_survived: u32,
+    pub money_earned: u32,
+    pub food_eaten: u32,
+    // (achievement id, unix timestamp)
+    pub unlocked: Vec<(String, u64)>,
+}
+
+impl Achievements {
+    pub fn create_empty() -> Self {
+        Achievements {
+            diamonds_mined: 0,
+            max_depth: 0,
+            pickaxe_level: 1,
+            creepers_survived: 0,
+            money_earned: 0,
+            food_eaten: 0,
+            unlocked: Vec::new(),
+        }
+    }
+
+    // Returns every achievement that got unlocked by this event
+    pub fn record(&mut self, event: GameEvent) -> Vec<&'static Achievement> {
+        match event {
+            GameEvent::OreMined(Ore::Diamond) => {
+                self.diamonds_mined = self.diamonds_mined.saturating_add(1)
+            }
+            GameEvent::Dug | GameEvent::OreMined(_) => (),
+            GameEvent::DepthReached(depth) => self.max_depth = self.max_depth.max(depth as u32),
+            GameEvent::PickaxeUpgraded(level) => {
+                self.pickaxe_level = self.pickaxe_level.max(level as u32)
+            }
+            GameEvent::CreeperSurvived => {
+                self.creepers_survived = self.creepers_survived.saturating_add(1)
+            }
+            GameEvent::MoneyEarned(amount) => {
+                self.money_earned = self.money_earned.saturating_add(amount.get())
+            }
+            GameEvent::FoodEaten => self.food_eaten = self.food_eaten.saturating_add(1),
+        }
+
+        let mut newly_unlocked = Vec::new();
+        for achievement in CATALOG.iter() {
+            if self.is_unlocked(achievement.id) {
+                continue;
+            }
+            let (current, target) = self.progress(&achievement.goal);
+            if current >= target {
+                self.unlocked.push((achievement.id.to_string(), now()));
+                newly_unlocked.push(achievement);
+            }
+        }
+        newly_unlocked
+    }
+
+    pub fn is_unlocked(&self, id: &str) -> bool {
+        self.unlocked
+            .iter()
+            .any(|(unlocked_id, _)| unlocked_id == id)
+    }
+
+    pub fn unlocked_at(&self, id: &str) -> Option<u64> {
+        self.unlocked
+            .iter()
+            .find(|(unlocked_id, _)| unlocked_id == id)
+            .map(|(_, timestamp)| *timestamp)
+    }
+
+    // (current, target)
+    pub fn progress(&self, goal: &Goal) -> (u32, u32) {
+        let (current, target) = match goal {
+            Goal::MineDiamonds(target) => (self.diamonds_mined, *target),
+            Goal::ReachDepth(target) => (self.max_depth, *target),
+            Goal::PickaxeLevel(target) => (self.pickaxe_level, *target),
+            Goal::SurviveCreepers(target) => (self.creepers_survived, *target),
+            Goal::EarnMoney(target) => (self.money_earned, *target),
+            Goal::EatFood(target) => (self.food_eaten, *target),
+        };
+        (current.min(target), target)
+    }
+
+    pub fn print_progress(&self) {
+        for achievement in CATALOG.iter() {
+            let (current, target) = self.progress(&achievement.goal);
+            let status = match self.unlocked_at(achievement.id) {
+                Some(timestamp) => tr!("achievement.unlocked_at", format_time(timestamp)),
+                None => format!("{}/{}", current, target),
+            };
+            println!(
+                "- {}: {} [{}]",
+                text(achievement.name),
+                text(achievement.description),
+                status
+            );
+        }
+    }
+}
+
+fn parse_achievement_string(achievement_string: &str) -> Result<Achievements, &'static str> {
+    let mut achievements = Achievements::create_empty();
+    // first token is the username
+    for token in achievement_string.split(';').skip(1) {
+        let Some((key, value)) = token.split_once(':') else {
+            continue;
+        };
+
+        match key {
+            "diamonds" => achievements.diamonds_mined = value.parse().unwrap_or(0),
+            "depth" => achievements.max_depth = value.parse().unwrap_or(0),
+            "pickaxe" => achievements.pickaxe_level = value.parse().unwrap_or(1),
+            "creepers" => achievements.creepers_survived = value.parse().unwrap_or(0),
+            "earned" => achievements.money_earned = value.parse().unwrap_or(0),
+            "eaten" => achievements.food_eaten = value.parse().unwrap_or(0),
+            id => {
+                if let Ok(timestamp) = value.parse() {
+                    achievements.unlocked.push((id.to_string(), timestamp));
+                }
+            }
+        }
+    }
+
+    Ok(achievements)
+}
+
+pub fn search_achievement_file(file_path: &str, username: &str) -> Result<Achievements, io::Error> {
+    let file = File::open(file_path)?;
+    let reader = io::BufReader::new(file);
+
+    for line_content in reader.lines().map_while(Result::ok) {
+        if line_content.split(';').next() != Some(username) {
+            continue;
+        }
+
+        if let Ok(achievements) = parse_achievement_string(&line_content) {
+            return Ok(achievements);
+        }
+    }
+
+    Ok(Achievements::create_empty())
+}
+
+pub fn update_achievement_file(players: &[Player], file_path: &str) -> io::Result<()> {
+    let mut file = File::create(file_path)?;
+
+    for player in players {
+        let achievements = &player.achievements;
+        let mut achievement_string = format!(
+            "{};diamonds:{};depth:{};pickaxe:{};creepers:{};earned:{};eaten:{};",
+            player.account.username,
+            achievements.diamonds_mined,
+            achievements.max_depth,
+            achievements.pickaxe_level,
+            achievements.creepers_survived,
+            achievements.money_earned,
+            achievements.food_eaten
+        );
+
+        for (id, timestamp) in &achievements.unlocked {
+            achievement_string.push_str(&format!("{}:{};", id, timestamp));
+        }
+
+        achievement_string.push('\n');
+        file.write_all(achievement_string.as_bytes())?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,517 @@
+// The auction house of a world. Sellers hand over what they list until the
+// auction ends and bidders pay their bid up front, getting it back when
+// someone bids more, so whatever happens at the end there is nothing left
+// to check. Auctions last a number of turns, and every dig anyone makes in
+// the world is one turn. An auction whose items fit neither in the bags nor
+// in the bank vault of whoever gets them is only settled once they do.
+use crate::bank::VAULT_SLOTS;
+use crate::inventory::Item;
+use crate::lang::tr;
+use crate::money::Money;
+use crate::player::Player;
+use crate::trade::find_player;
+use std::fs::File;
+use std::io::{self, BufRead, Write};
+
+pub const DEFAULT_DURATION: u64 = 50;
+pub const MAX_DURATION: u64 = 500;
+// Open listings per account
+pub const MAX_LISTINGS: usize = 5;
+
+pub struct Bid {
+    pub bidder: String,
+    pub amount: Money,
+}
+
+pub struct Listing {
+    pub id: u32,
+    pub seller: String,
+    pub item: Item,
+    pub count: u32,
+    pub start_price: Money,
+    // the turn the auction ends on
+    pub ends: u64,
+    // the highest one so far
+    pub bid: Option<Bid>,
+}
+
+#[derive(Default)]
+pub struct AuctionHouse {
+    pub turn: u64,
+    pub listings: Vec<Listing>,
+}
+
+impl Listing {
+    // What is up for auction, like `2 Diamond`
+    pub fn describe(&self) -> String {
+        format!("{} {}", self.count, self.item.name())
+    }
+
+    pub fn minimum_bid(&self) -> Money {
+        match &self.bid {
+            Some(bid) => bid.amount + Money::new(1),
+            None => self.start_price,
+        }
+    }
+
+    pub fn turns_left(&self, house: &AuctionHouse) -> u64 {
+        self.ends.saturating_sub(house.turn)
+    }
+}
+
+impl AuctionHouse {
+    pub fn listings_of<'a>(&'a self, username: &'a str) -> impl Iterator<Item = &'a Listing> {
+        self.listings
+            .iter()
+            .filter(move |listing| listing.seller == username)
+    }
+}
+
+pub fn list(
+    player: &mut Player,
+    house: &mut AuctionHouse,
+    item: Item,
+    count: u32,
+    start_price: Money,
+    duration: u64,
+    log: &mut Vec<String>,
+) -> bool {
+    let username = player.account.username.clone();
+    if count == 0 {
+        log.push(tr!("auction.nothing_listed"));
+        return false;
+    }
+    if start_price.is_zero() {
+        log.push(tr!("auction.no_price"));
+        return false;
+    }
+    if duration == 0 || duration > MAX_DURATION {
+        log.push(tr!("auction.bad_duration", MAX_DURATION));
+        return false;
+    }
+    if house.listings_of(&username).count() >= MAX_LISTINGS {
+        log.push(tr!("auction.too_many", MAX_LISTINGS));
+        return false;
+    }
+    if player.inventory.count_item(item) < count {
+        log.push(tr!("trade.you_lack", format!("{} {}", count, item.name())));
+        return false;
+    }
+
+    let newest = house.listings.iter().map(|x| x.id).max().unwrap_or(0);
+    let Some(id) = newest.checked_add(1) else {
+        log.push(tr!("auction.no_ids_left"));
+        return false;
+    };
+
+    for _ in 0..count {
+        player.inventory.remove_item(item);
+    }
+    let listing = Listing {
+        id,
+        seller: username,
+        item,
+        count,
+        start_price,
+        ends: house.turn.saturating_add(duration),
+        bid: None,
+    };
+    log.push(tr!(
+        "auction.listed",
+        listing.id,
+        listing.describe(),
+        start_price,
+        duration
+    ));
+    house.listings.push(listing);
+    true
+}
+
+pub fn bid(
+    players: &mut [Player],
+    house: &mut AuctionHouse,
+    username: &str,
+    id: u32,
+    amount: Money,
+    log: &mut Vec<String>,
+) -> bool {
+    let turn = house.turn;
+    let Some(listing) = house.listings.iter_mut().find(|x| x.id == id) else {
+        log.push(tr!("auction.no_such_listing", id));
+        return false;
+    };
+    if listing.ends <= turn {
+        log.push(tr!("auction.over", id));
+        return false;
+    }
+    if listing.seller == username {
+        log.push(tr!("auction.own_listing"));
+        return false;
+    }
+    if amount < listing.minimum_bid() {
+        log.push(tr!("auction.bid_too_low", listing.minimum_bid()));
+        return false;
+    }
+    let Some(index) = find_player(players, username) else {
+        log.push(tr!("trade.no_such_account", username));
+        return false;
+    };
+    // raising your own bid only costs the difference
+    let refund = match &listing.bid {
+        Some(bid) if bid.bidder == username => bid.amount,
+        _ => Money::ZERO,
+    };
+    if players[index].account.money + refund < amount {
+        log.push(tr!("auction.cant_afford", amount));
+        return false;
+    }
+
+    if let Some(previous) = listing.bid.take() {
+        if let Some(outbid) = find_player(players, &previous.bidder) {
+            players[outbid].account.money += previous.amount;
+        }
+    }
+    let bidder = &mut players[index].account;
+    bidder.money = bidder.money.saturating_sub(amount);
+    listing.bid = Some(Bid {
+        bidder: username.to_string(),
+        amount,
+    });
+    log.push(tr!("auction.bid_placed", amount, listing.describe(), id));
+    true
+}
+
+// Takes back a listing nobody has bid on yet
+pub fn withdraw(
+    players: &mut [Player],
+    house: &mut AuctionHouse,
+    username: &str,
+    id: u32,
+    log: &mut Vec<String>,
+) -> bool {
+    let Some(position) = house
+        .listings
+        .iter()
+        .position(|x| x.id == id && x.seller == username)
+    else {
+        log.push(tr!("auction.no_such_listing", id));
+        return false;
+    };
+    if house.listings[position].bid.is_some() {
+        log.push(tr!("auction.has_bids"));
+        return false;
+    }
+    if let Some(index) = find_player(players, username) {
+        let listing = &house.listings[position];
+        if !has_room_for(&players[index], listing) {
+            log.push(tr!("auction.no_room", listing.describe()));
+            return false;
+        }
+    }
+
+    let listing = house.listings.remove(position);
+    log.push(tr!("auction.withdrawn", listing.id, listing.describe()));
+    if let Some(index) = find_player(players, username) {
+        deliver(&mut players[index], &listing, log);
+    }
+    true
+}
+
+// One turn passes, auctions that are over get settled. Only what happened
+// to `username` goes to the log, the others find out from their pockets.
+pub fn advance(
+    players: &mut [Player],
+    house: &mut AuctionHouse,
+    username: &str,
+    log: &mut Vec<String>,
+) {
+    house.turn = house.turn.saturating_add(1);
+    let turn = house.turn;
+    let (ended, open): (Vec<Listing>, Vec<Listing>) = house
+        .listings
+        .drain(..)
+        .partition(|listing| listing.ends <= turn);
+    house.listings = open;
+
+    for listing in ended {
+        let receiver = match &listing.bid {
+            Some(bid) => &bid.bidder,
+            None => &listing.seller,
+        };
+        let waiting = find_player(players, receiver)
+            .is_some_and(|index| !has_room_for(&players[index], &listing));
+        if waiting {
+            // only told once, not on every turn it keeps waiting
+            if listing.ends == turn && receiver == username {
+                log.push(tr!("auction.waiting", listing.id, listing.describe()));
+            }
+            house.listings.push(listing);
+            continue;
+        }
+        settle(players, listing, username, log);
+    }
+}
+
+// Room for all of it in the bags and the bank vault together
+fn has_room_for(player: &Player, listing: &Listing) -> bool {
+    let vault_room = VAULT_SLOTS.saturating_sub(player.vault.used_slots());
+    player
+        .inventory
+        .free_space(listing.item)
+        .saturating_add(vault_room)
+        >= listing.count
+}
+
+fn settle(players: &mut [Player], listing: Listing, username: &str, log: &mut Vec<String>) {
+    let mut unseen = Vec::new();
+    let Some(bid) = &listing.bid else {
+        let log = if listing.seller == username {
+            log
+        } else {
+            &mut unseen
+        };
+        log.push(tr!("auction.unsold", listing.id, listing.describe()));
+        if let Some(seller) = find_player(players, &listing.seller) {
+            deliver(&mut players[seller], &listing, log);
+        }
+        return;
+    };
+
+    if let Some(seller) = find_player(players, &listing.seller) {
+        players[seller].account.money += bid.amount;
+    }
+    if listing.seller == username {
+        log.push(tr!(
+            "auction.sold",
+            listing.id,
+            listing.describe(),
+            bid.bidder,
+            bid.amount
+        ));
+    }
+    let log = if bid.bidder == username {
+        log
+    } else {
+        &mut unseen
+    };
+    log.push(tr!(
+        "auction.won",
+        listing.id,
+        listing.describe(),
+        bid.amount
+    ));
+    if let Some(winner) = find_player(players, &bid.bidder) {
+        deliver(&mut players[winner], &listing, log);
+    }
+}
+
+// Into the bags, and whatever doesn't fit waits in the bank vault
+fn deliver(player: &mut Player, listing: &Listing, log: &mut Vec<String>) {
+    let mut overflow = 0;
+    for _ in 0..listing.count {
+        if !player.inventory.push_item(listing.item) {
+            overflow += 1;
+        }
+    }
+    if overflow > 0 {
+        player.vault.goods.add_item(listing.item, overflow);
+        log.push(tr!("auction.sent_to_vault", overflow, listing.item.name()));
+    }
+}
+
+// `id;seller;item;count;start_price;ends;bidder;amount`, the bidder and
+// amount are empty while nobody has bid
+fn parse_listing_string(listing_string: &str) -> Result<Listing, &'static str> {
+    let tokens: Vec<&str> = listing_string.split(';').collect();
+    if tokens.len() != 8 {
+        return Err("Invalid number of tokens");
+    }
+
+    let bid = match tokens[6] {
+        "" => None,
+        bidder => Some(Bid {
+            bidder: bidder.to_string(),
+            amount: tokens[7].parse().map_err(|_| "Invalid bid")?,
+        }),
+    };
+    Ok(Listing {
+        id: tokens[0].parse().map_err(|_| "Invalid id")?,
+        seller: tokens[1].to_string(),
+        item: Item::from_string(tokens[2]).ok_or("Invalid item")?,
+        count: tokens[3].parse().map_err(|_| "Invalid count")?,
+        start_price: tokens[4].parse().map_err(|_| "Invalid price")?,
+        ends: tokens[5].parse().map_err(|_| "Invalid turn")?,
+        bid,
+    })
+}
+
+// The first line holds the turn, then one listing per line
+pub fn parse_auction_file(file_path: &str) -> io::Result<AuctionHouse> {
+    let file = File::open(file_path)?;
+    let mut lines = io::BufReader::new(file).lines().map_while(Result::ok);
+    let mut house = AuctionHouse {
+        turn: lines
+            .next()
+            .and_then(|x| x.trim().parse().ok())
+            .unwrap_or(0),
+        listings: Vec::new(),
+    };
+
+    for line_content in lines {
+        if let Ok(listing) = parse_listing_string(&line_content) {
+            house.listings.push(listing);
+        }
+    }
+
+    Ok(house)
+}
+
+pub fn update_auction_file(house: &AuctionHouse, file_path: &str) -> io::Result<()> {
+    let mut file = File::create(file_path)?;
+    writeln!(file, "{}", house.turn)?;
+
+    for listing in &house.listings {
+        let (bidder, amount) = match &listing.bid {
+            Some(bid) => (bid.bidder.as_str(), bid.amount.to_string()),
+            None => ("", String::new()),
+        };
+        let listing_string = format!(
+            "{};{};{};{};{};{};{};{}\n",
+            listing.id,
+            listing.seller,
+            listing.item.to_string(),
+            listing.count,
+            listing.start_price,
+            listing.ends,
+            bidder,
+            amount
+        );
+        file.write_all(listing_string.as_bytes())?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::account::Account;
+    use crate::inventory::{Inventory, Ore};
+
+    const COAL: Item = Item::Ore(Ore::Coal);
+
+    fn player(username: &str) -> Player {
+        Player::new(
+            Inventory::create_empty(),
+            Account::new_account(username.to_string()),
+        )
+    }
+
+    // seller puts up 1 diamond for at least $10, over after one turn
+    fn house(players: &mut [Player]) -> AuctionHouse {
+        let mut house = AuctionHouse::default();
+        let diamond = Item::Ore(Ore::Diamond);
+        players[0].inventory.push_item(diamond);
+        let listed = list(
+            &mut players[0],
+            &mut house,
+            diamond,
+            1,
+            Money::new(10),
+            1,
+            &mut Vec::new(),
+        );
+        assert!(listed);
+        house
+    }
+
+    #[test]
+    fn being_outbid_gives_the_escrowed_bid_back() {
+        let mut players = vec![player("seller"), player("alice"), player("bob")];
+        let mut house = house(&mut players);
+        let id = house.listings[0].id;
+
+        assert!(bid(
+            &mut players,
+            &mut house,
+            "alice",
+            id,
+            Money::new(40),
+            &mut Vec::new()
+        ));
+        assert_eq!(players[1].account.money, Money::new(60));
+
+        assert!(bid(
+            &mut players,
+            &mut house,
+            "bob",
+            id,
+            Money::new(50),
+            &mut Vec::new()
+        ));
+        assert_eq!(players[1].account.money, Money::new(100));
+        assert_eq!(players[2].account.money, Money::new(50));
+    }
+
+    #[test]
+    fn raising_your_own_bid_only_costs_the_difference() {
+        let mut players = vec![player("seller"), player("alice")];
+        let mut house = house(&mut players);
+        let id = house.listings[0].id;
+
+        assert!(bid(
+            &mut players,
+            &mut house,
+            "alice",
+            id,
+            Money::new(40),
+            &mut Vec::new()
+        ));
+        assert!(bid(
+            &mut players,
+            &mut house,
+            "alice",
+            id,
+            Money::new(100),
+            &mut Vec::new()
+        ));
+        assert_eq!(players[1].account.money, Money::ZERO);
+    }
+
+    #[test]
+    fn a_listing_waits_for_room_before_it_is_delivered() {
+        let mut players = vec![player("seller"), player("alice")];
+        let mut house = house(&mut players);
+        let id = house.listings[0].id;
+        assert!(bid(
+            &mut players,
+            &mut house,
+            "alice",
+            id,
+            Money::new(10),
+            &mut Vec::new()
+        ));
+
+        let winner = &mut players[1];
+        while winner.inventory.push_item(COAL) {}
+        winner.vault.goods.add_item(COAL, VAULT_SLOTS);
+
+        let mut log = Vec::new();
+        advance(&mut players, &mut house, "alice", &mut log);
+        assert_eq!(house.listings.len(), 1);
+        assert_eq!(log, vec![tr!("auction.waiting", id, "1 Diamond")]);
+        assert_eq!(players[0].account.money, Money::new(100));
+
+        // told only once while it keeps waiting
+        let mut log = Vec::new();
+        advance(&mut players, &mut house, "alice", &mut log);
+        assert!(log.is_empty());
+        assert_eq!(house.listings.len(), 1);
+
+        players[1].inventory.remove_item(COAL);
+        advance(&mut players, &mut house, "alice", &mut Vec::new());
+        assert!(house.listings.is_empty());
+        assert_eq!(players[1].inventory.count_ore(Ore::Diamond), 1);
+        assert_eq!(players[0].account.money, Money::new(110));
+    }
+}
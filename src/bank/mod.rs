@@ -0,0 +1,266 @@
+// The vault every account has at the bank. Whatever is stored there stays
+// on the surface, away from the dangers of the mine, and the money in it
+// earns interest for every day it sits there.
+use crate::lang::tr;
+use crate::money::Money;
+use crate::player::Player;
+use crate::timestamp::now;
+use crate::trade::{has_room, owns, Goods};
+use std::fs::File;
+use std::io::{self, BufRead, Write};
+
+// Pieces of ore, food and gear a vault holds
+pub const VAULT_SLOTS: u32 = 50;
+// Paid daily on the money in the vault, in tenths of a percent
+const INTEREST_PER_MILLE: u64 = 10;
+// Days of interest paid at most when the vault is visited, so an account
+// left alone for months doesn't come back rich
+const MAX_INTEREST_DAYS: u64 = 30;
+const SECONDS_PER_DAY: u64 = 86400;
+// Entries kept on the statement
+const STATEMENT_LENGTH: usize = 20;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum EntryKind {
+    Deposit,
+    Withdrawal,
+    Interest,
+}
+
+pub struct Entry {
+    pub time: u64,
+    pub kind: EntryKind,
+    pub goods: Goods,
+}
+
+pub struct Vault {
+    pub goods: Goods,
+    // when interest was last paid, a unix timestamp
+    pub interest_paid: u64,
+    // most recent last
+    pub statement: Vec<Entry>,
+}
+
+impl EntryKind {
+    pub fn to_string(self) -> &'static str {
+        match self {
+            EntryKind::Deposit => "deposit",
+            EntryKind::Withdrawal => "withdrawal",
+            EntryKind::Interest => "interest",
+        }
+    }
+
+    fn from_string(string: &str) -> Option<EntryKind> {
+        [
+            EntryKind::Deposit,
+            EntryKind::Withdrawal,
+            EntryKind::Interest,
+        ]
+        .into_iter()
+        .find(|kind| kind.to_string() == string)
+    }
+}
+
+impl Vault {
+    pub fn create_empty() -> Self {
+        Vault {
+            goods: Goods::default(),
+            interest_paid: now(),
+            statement: Vec::new(),
+        }
+    }
+
+    pub fn used_slots(&self) -> u32 {
+        self.goods.pieces(|_| true)
+    }
+
+    fn record(&mut self, kind: EntryKind, goods: Goods) {
+        self.statement.push(Entry {
+            time: now(),
+            kind,
+            goods,
+        });
+        if self.statement.len() > STATEMENT_LENGTH {
+            self.statement.remove(0);
+        }
+    }
+
+    fn holds(&self, goods: &Goods) -> bool {
+        self.goods.money >= goods.money
+            && goods
+                .items
+                .iter()
+                .all(|(item, count)| self.goods.count(*item) >= *count)
+    }
+}
+
+// Zero of an item would only leave empty entries in the vault and statement
+fn moves_something(goods: &Goods) -> bool {
+    !goods.is_empty() && goods.items.iter().all(|(_, count)| *count > 0)
+}
+
+// Pays what the money earned since the last visit, returns the interest
+pub fn pay_interest(player: &mut Player, log: &mut Vec<String>) -> Money {
+    let vault = &mut player.vault;
+    let days = now().saturating_sub(vault.interest_paid) / SECONDS_PER_DAY;
+    if days == 0 {
+        return Money::ZERO;
+    }
+    vault.interest_paid += days * SECONDS_PER_DAY;
+
+    let start = vault.goods.money.get() as u64;
+    let mut balance = start;
+    for _ in 0..days.min(MAX_INTEREST_DAYS) {
+        balance += balance * INTEREST_PER_MILLE / 1000;
+    }
+    let interest = Money::new((balance - start).min(u32::MAX as u64) as u32);
+    if interest.is_zero() {
+        return interest;
+    }
+
+    vault.goods.money += interest;
+    let goods = Goods {
+        money: interest,
+        items: Vec::new(),
+    };
+    vault.record(EntryKind::Interest, goods);
+    log.push(tr!("bank.interest_paid", interest));
+    interest
+}
+
+pub fn deposit(player: &mut Player, goods: Goods, log: &mut Vec<String>) -> bool {
+    if !moves_something(&goods) {
+        log.push(tr!("bank.zero_amount"));
+        return false;
+    }
+    if !owns(player, &goods) {
+        log.push(tr!("trade.you_lack", goods.describe()));
+        return false;
+    }
+    let pieces = goods.pieces(|_| true);
+    if player.vault.used_slots().saturating_add(pieces) > VAULT_SLOTS {
+        log.push(tr!("bank.vault_full"));
+        return false;
+    }
+
+    player.account.money = player.account.money.saturating_sub(goods.money);
+    player.vault.goods.money += goods.money;
+    for (item, count) in &goods.items {
+        for _ in 0..*count {
+            player.inventory.remove_item(*item);
+        }
+        player.vault.goods.add_item(*item, *count);
+    }
+
+    log.push(tr!("bank.deposited", goods.describe()));
+    player.vault.record(EntryKind::Deposit, goods);
+    true
+}
+
+pub fn withdraw(player: &mut Player, goods: Goods, log: &mut Vec<String>) -> bool {
+    if !moves_something(&goods) {
+        log.push(tr!("bank.zero_amount"));
+        return false;
+    }
+    if !player.vault.holds(&goods) {
+        log.push(tr!("bank.not_in_vault", goods.describe()));
+        return false;
+    }
+    if !has_room(player, &goods, &Goods::default()) {
+        log.push(tr!("trade.you_have_no_room"));
+        return false;
+    }
+
+    let vault = &mut player.vault.goods;
+    vault.money = vault.money.saturating_sub(goods.money);
+    player.account.money += goods.money;
+    for (item, count) in &goods.items {
+        for _ in 0..*count {
+            player.inventory.push_item(*item);
+        }
+    }
+    vault.items = vault
+        .items
+        .iter()
+        .map(|&(item, count)| (item, count - goods.count(item)))
+        .filter(|(_, count)| *count > 0)
+        .collect();
+
+    log.push(tr!("bank.withdrew", goods.describe()));
+    player.vault.record(EntryKind::Withdrawal, goods);
+    true
+}
+
+// `username;goods;interest_paid;time|kind|goods/...`
+fn parse_vault_string(vault_string: &str) -> Result<Vault, &'static str> {
+    let tokens: Vec<&str> = vault_string.split(';').collect();
+    if tokens.len() != 4 {
+        return Err("Invalid number of tokens");
+    }
+
+    let mut vault = Vault {
+        goods: Goods::from_save_string(tokens[1])?,
+        interest_paid: tokens[2].parse().map_err(|_| "Invalid time")?,
+        statement: Vec::new(),
+    };
+    for entry in tokens[3].split('/').filter(|x| !x.is_empty()) {
+        let fields: Vec<&str> = entry.split('|').collect();
+        if fields.len() != 3 {
+            return Err("Invalid statement entry");
+        }
+        vault.statement.push(Entry {
+            time: fields[0].parse().map_err(|_| "Invalid time")?,
+            kind: EntryKind::from_string(fields[1]).ok_or("Invalid entry kind")?,
+            goods: Goods::from_save_string(fields[2])?,
+        });
+    }
+    Ok(vault)
+}
+
+pub fn search_vault_file(file_path: &str, username: &str) -> Result<Vault, io::Error> {
+    let file = File::open(file_path)?;
+    let reader = io::BufReader::new(file);
+
+    for line_content in reader.lines().map_while(Result::ok) {
+        if line_content.split(';').next() != Some(username) {
+            continue;
+        }
+
+        if let Ok(vault) = parse_vault_string(&line_content) {
+            return Ok(vault);
+        }
+    }
+
+    Ok(Vault::create_empty())
+}
+
+pub fn update_vault_file(players: &[Player], file_path: &str) -> io::Result<()> {
+    let mut file = File::create(file_path)?;
+
+    for player in players {
+        let vault = &player.vault;
+        let statement: Vec<String> = vault
+            .statement
+            .iter()
+            .map(|entry| {
+                format!(
+                    "{}|{}|{}",
+                    entry.time,
+                    entry.kind.to_string(),
+                    entry.goods.to_save_string()
+                )
+            })
+            .collect();
+        let vault_string = format!(
+            "{};{};{};{}\n",
+            player.account.username,
+            vault.goods.to_save_string(),
+            vault.interest_paid,
+            statement.join("/")
+        );
+
+        file.write_all(vault_string.as_bytes())?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,30 @@
+use std::io;
+use text_craft::world::{self, WORLDS_DIR};
+use text_craft::{lang, server};
+
+fn main() -> Result<(), io::Error> {
+    let args: Vec<String> = std::env::args().collect();
+    let option = |name: &str| {
+        let index = args.iter().position(|arg| arg == name)?;
+        args.get(index + 1).map(|x| x.as_str())
+    };
+
+    // --lang <code> picks the language of the log messages sent to players
+    if args.iter().any(|arg| arg == "--lang") {
+        if let Err(msg) = lang::set_language(option("--lang").unwrap_or_default()) {
+            let codes: Vec<&str> = lang::LANGUAGES.iter().map(|(code, _)| *code).collect();
+            eprintln!("{} (available: {})", msg, codes.join(", "));
+            std::process::exit(2);
+        }
+    }
+
+    world::import_legacy_save(WORLDS_DIR)?;
+    // --world <name> picks the world to host, the last played one otherwise
+    let world = world::open_world_or_default(WORLDS_DIR, option("--world"))?;
+
+    // --address <host:port> to listen on, only this machine by default
+    server::run(
+        world,
+        option("--address").unwrap_or(server::DEFAULT_ADDRESS),
+    )
+}
@@ -0,0 +1,89 @@
+// The time of day in a world. It only moves when someone does something,
+// every dig, craft and trip to or from the mine takes a while. Nights are
+// more dangerous in the mine and the shop keeps daytime hours.
+use crate::lang::tr;
+
+pub const MINUTES_PER_DAY: u64 = 24 * 60;
+pub const DIG_MINUTES: u64 = 10;
+pub const CRAFT_MINUTES: u64 = 30;
+pub const COOK_MINUTES: u64 = 15;
+// Walking between the town and the mine entrance
+pub const TRAVEL_MINUTES: u64 = 20;
+
+// Hours of the day
+const DAWN: u64 = 6;
+const DUSK: u64 = 20;
+const SHOP_OPENS: u64 = 8;
+const SHOP_CLOSES: u64 = 18;
+// New worlds start on the morning of the first day
+const START: u64 = 7 * 60;
+
+#[derive(Clone, Copy)]
+pub struct Clock {
+    // since midnight of the first day
+    pub minutes: u64,
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Clock { minutes: START }
+    }
+}
+
+impl Clock {
+    pub fn advance(&mut self, minutes: u64) {
+        self.minutes = self.minutes.saturating_add(minutes);
+    }
+
+    pub fn day(self) -> u64 {
+        self.minutes / MINUTES_PER_DAY + 1
+    }
+
+    fn hour(self) -> u64 {
+        self.minutes % MINUTES_PER_DAY / 60
+    }
+
+    // `HH:MM`
+    pub fn time(self) -> String {
+        format_hour_minute(self.minutes % MINUTES_PER_DAY)
+    }
+
+    pub fn is_night(self) -> bool {
+        self.hour() < DAWN || self.hour() >= DUSK
+    }
+
+    pub fn is_shop_open(self) -> bool {
+        (SHOP_OPENS..SHOP_CLOSES).contains(&self.hour())
+    }
+
+    // 0 while the shop is open
+    pub fn until_shop_opens(self) -> u64 {
+        if self.is_shop_open() {
+            return 0;
+        }
+        let now = self.minutes % MINUTES_PER_DAY;
+        let opens = SHOP_OPENS * 60;
+        (opens + MINUTES_PER_DAY - now) % MINUTES_PER_DAY
+    }
+
+    // Like `Day 3, 21:40 (night)`
+    pub fn describe(self) -> String {
+        match self.is_night() {
+            true => tr!("clock.night", self.day(), self.time()),
+            false => tr!("clock.day", self.day(), self.time()),
+        }
+    }
+}
+
+// Why the shop won't serve anyone right now
+pub fn shop_closed_message() -> String {
+    tr!(
+        "clock.shop_closed",
+        format_hour_minute(SHOP_OPENS * 60),
+        format_hour_minute(SHOP_CLOSES * 60)
+    )
+}
+
+fn format_hour_minute(minutes: u64) -> String {
+    format!("{:02}:{:02}", minutes / 60, minutes % 60)
+}
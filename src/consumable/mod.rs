@@ -0,0 +1,84 @@
+// Items that are used up in the mine: potions, TNT, torches and rope.
+// They are bought at the shop and kept in their own bag, what each one
+// does when used is up to the game module.
+use crate::lang::text;
+use crate::money::Money;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Consumable {
+    HealingPotion,
+    NightVisionPotion,
+    FireResistancePotion,
+    Tnt,
+    Torch,
+    Rope,
+}
+
+pub const ALL_CONSUMABLES: [Consumable; 6] = [
+    Consumable::HealingPotion,
+    Consumable::NightVisionPotion,
+    Consumable::FireResistancePotion,
+    Consumable::Tnt,
+    Consumable::Torch,
+    Consumable::Rope,
+];
+
+impl Consumable {
+    pub fn price(self) -> Money {
+        Money::new(match self {
+            Consumable::HealingPotion => 80,
+            Consumable::NightVisionPotion => 60,
+            Consumable::FireResistancePotion => 70,
+            Consumable::Tnt => 100,
+            Consumable::Torch => 10,
+            Consumable::Rope => 40,
+        })
+    }
+
+    // Only of any use down in the mine, potions work anywhere
+    pub fn mine_only(self) -> bool {
+        matches!(self, Consumable::Tnt | Consumable::Torch | Consumable::Rope)
+    }
+
+    // Name shown to the player, to_string is what gets saved
+    pub fn name(self) -> &'static str {
+        text(match self {
+            Consumable::HealingPotion => "consumable.healing_potion",
+            Consumable::NightVisionPotion => "consumable.night_vision_potion",
+            Consumable::FireResistancePotion => "consumable.fire_resistance_potion",
+            Consumable::Tnt => "consumable.tnt",
+            Consumable::Torch => "consumable.torch",
+            Consumable::Rope => "consumable.rope",
+        })
+    }
+
+    // What it does, shown in the shop
+    pub fn description(self) -> &'static str {
+        text(match self {
+            Consumable::HealingPotion => "consumable.healing_potion.description",
+            Consumable::NightVisionPotion => "consumable.night_vision_potion.description",
+            Consumable::FireResistancePotion => "consumable.fire_resistance_potion.description",
+            Consumable::Tnt => "consumable.tnt.description",
+            Consumable::Torch => "consumable.torch.description",
+            Consumable::Rope => "consumable.rope.description",
+        })
+    }
+
+    pub fn to_string(self) -> &'static str {
+        match self {
+            Consumable::HealingPotion => "Healing Potion",
+            Consumable::NightVisionPotion => "Night Vision Potion",
+            Consumable::FireResistancePotion => "Fire Resistance Potion",
+            Consumable::Tnt => "TNT",
+            Consumable::Torch => "Torch",
+            Consumable::Rope => "Rope",
+        }
+    }
+
+    pub fn from_string(string: &str) -> Option<Consumable> {
+        ALL_CONSUMABLES
+            .iter()
+            .copied()
+            .find(|consumable| consumable.to_string() == string)
+    }
+}
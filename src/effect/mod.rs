@@ -0,0 +1,118 @@
+use crate::lang::text;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum EffectKind {
+    Poison,
+    Burning,
+    Regeneration,
+    Haste,
+    NightVision,
+    FireResistance,
+    // from a lit torch
+    Torchlight,
+}
+
+#[derive(Clone, Copy)]
+pub struct StatusEffect {
+    pub kind: EffectKind,
+    pub turns_left: u8,
+}
+
+// What the active effects did during a single turn
+pub struct EffectTick {
+    pub damage: u8,
+    pub heal: u8,
+    pub expired: Vec<EffectKind>,
+}
+
+pub struct StatusEffects {
+    pub active: Vec<StatusEffect>,
+}
+
+impl EffectKind {
+    pub fn name(self) -> &'static str {
+        text(match self {
+            EffectKind::Poison => "effect.poison",
+            EffectKind::Burning => "effect.burning",
+            EffectKind::Regeneration => "effect.regeneration",
+            EffectKind::Haste => "effect.haste",
+            EffectKind::NightVision => "effect.night_vision",
+            EffectKind::FireResistance => "effect.fire_resistance",
+            EffectKind::Torchlight => "effect.torchlight",
+        })
+    }
+
+    fn damage_per_turn(self) -> u8 {
+        match self {
+            EffectKind::Poison => 4,
+            EffectKind::Burning => 6,
+            _ => 0,
+        }
+    }
+
+    fn heal_per_turn(self) -> u8 {
+        match self {
+            EffectKind::Regeneration => 5,
+            _ => 0,
+        }
+    }
+}
+
+impl StatusEffects {
+    pub fn create_empty() -> Self {
+        StatusEffects { active: Vec::new() }
+    }
+
+    // Applying an effect that is already active only refreshes its duration
+    pub fn apply(&mut self, kind: EffectKind, turns: u8) {
+        match self.active.iter_mut().find(|effect| effect.kind == kind) {
+            Some(effect) => effect.turns_left = effect.turns_left.max(turns),
+            None => self.active.push(StatusEffect {
+                kind,
+                turns_left: turns,
+            }),
+        }
+    }
+
+    pub fn remove(&mut self, kind: EffectKind) -> bool {
+        let before = self.active.len();
+        self.active.retain(|effect| effect.kind != kind);
+        self.active.len() != before
+    }
+
+    pub fn has(&self, kind: EffectKind) -> bool {
+        self.active.iter().any(|effect| effect.kind == kind)
+    }
+
+    pub fn tick(&mut self) -> EffectTick {
+        let mut result = EffectTick {
+            damage: 0,
+            heal: 0,
+            expired: Vec::new(),
+        };
+
+        for effect in &mut self.active {
+            result.damage = result.damage.saturating_add(effect.kind.damage_per_turn());
+            result.heal = result.heal.saturating_add(effect.kind.heal_per_turn());
+            effect.turns_left = effect.turns_left.saturating_sub(1);
+            if effect.turns_left == 0 {
+                result.expired.push(effect.kind);
+            }
+        }
+
+        self.active.retain(|effect| effect.turns_left > 0);
+        result
+    }
+
+    pub fn describe(&self) -> String {
+        if self.active.is_empty() {
+            return text("common.none").to_string();
+        }
+
+        self.active
+            .iter()
+            .map(|effect| format!("{} ({})", effect.kind.name(), effect.turns_left))
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+}
@@ -0,0 +1,281 @@
+use crate::inventory::Ore;
+use crate::lang::text;
+use crate::money::Money;
+use crate::player::Player;
+use crate::style;
+use std::fs::File;
+use std::io::{self, BufRead, Write};
+
+// Highest combined damage reduction, in percent
+const MAX_REDUCTION: u32 = 80;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum DamageKind {
+    Explosion,
+    Bite,
+    Fire,
+    Starvation,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Slot {
+    Helmet,
+    Chestplate,
+    Boots,
+    Weapon,
+    Light,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Gear {
+    IronHelmet,
+    IronChestplate,
+    IronBoots,
+    IronSword,
+    Lantern,
+}
+
+pub const ALL_GEAR: [Gear; 5] = [
+    Gear::IronHelmet,
+    Gear::IronChestplate,
+    Gear::IronBoots,
+    Gear::IronSword,
+    Gear::Lantern,
+];
+
+pub const ALL_SLOTS: [Slot; 5] = [
+    Slot::Helmet,
+    Slot::Chestplate,
+    Slot::Boots,
+    Slot::Weapon,
+    Slot::Light,
+];
+
+pub struct Equipment {
+    pub helmet: Option<Gear>,
+    pub chestplate: Option<Gear>,
+    pub boots: Option<Gear>,
+    pub weapon: Option<Gear>,
+    pub light: Option<Gear>,
+}
+
+impl Slot {
+    pub fn name(self) -> &'static str {
+        text(match self {
+            Slot::Helmet => "slot.helmet",
+            Slot::Chestplate => "slot.chestplate",
+            Slot::Boots => "slot.boots",
+            Slot::Weapon => "slot.weapon",
+            Slot::Light => "slot.light",
+        })
+    }
+
+    pub fn to_string(self) -> &'static str {
+        match self {
+            Slot::Helmet => "Helmet",
+            Slot::Chestplate => "Chestplate",
+            Slot::Boots => "Boots",
+            Slot::Weapon => "Weapon",
+            Slot::Light => "Light source",
+        }
+    }
+
+    fn from_string(string: &str) -> Option<Slot> {
+        ALL_SLOTS
+            .iter()
+            .copied()
+            .find(|slot| slot.to_string() == string)
+    }
+}
+
+impl Gear {
+    // Name shown to the player, to_string is what gets saved
+    pub fn name(self) -> &'static str {
+        text(match self {
+            Gear::IronHelmet => "gear.iron_helmet",
+            Gear::IronChestplate => "gear.iron_chestplate",
+            Gear::IronBoots => "gear.iron_boots",
+            Gear::IronSword => "gear.iron_sword",
+            Gear::Lantern => "gear.lantern",
+        })
+    }
+
+    pub fn to_string(self) -> &'static str {
+        match self {
+            Gear::IronHelmet => "Iron Helmet",
+            Gear::IronChestplate => "Iron Chestplate",
+            Gear::IronBoots => "Iron Boots",
+            Gear::IronSword => "Iron Sword",
+            Gear::Lantern => "Lantern",
+        }
+    }
+
+    pub fn from_string(string: &str) -> Option<Gear> {
+        ALL_GEAR
+            .iter()
+            .copied()
+            .find(|gear| gear.to_string() == string)
+    }
+
+    pub fn slot(self) -> Slot {
+        match self {
+            Gear::IronHelmet => Slot::Helmet,
+            Gear::IronChestplate => Slot::Chestplate,
+            Gear::IronBoots => Slot::Boots,
+            Gear::IronSword => Slot::Weapon,
+            Gear::Lantern => Slot::Light,
+        }
+    }
+
+    pub fn price(self) -> Money {
+        Money::new(match self {
+            Gear::IronHelmet => 150,
+            Gear::IronChestplate => 250,
+            Gear::IronBoots => 120,
+            Gear::IronSword => 180,
+            Gear::Lantern => 60,
+        })
+    }
+
+    // Ores needed to craft this instead of buying it
+    pub fn recipe(self) -> (Ore, u32) {
+        match self {
+            Gear::IronHelmet => (Ore::IronOre, 5),
+            Gear::IronChestplate => (Ore::IronOre, 8),
+            Gear::IronBoots => (Ore::IronOre, 4),
+            Gear::IronSword => (Ore::IronOre, 3),
+            Gear::Lantern => (Ore::GoldOre, 1),
+        }
+    }
+
+    // Damage reduction in percent against the given kind of damage
+    pub fn reduction(self, kind: DamageKind) -> u32 {
+        match (self, kind) {
+            (Gear::IronHelmet, DamageKind::Explosion) => 20,
+            (Gear::IronChestplate, DamageKind::Explosion) => 35,
+            (Gear::IronChestplate, DamageKind::Bite) => 20,
+            (Gear::IronBoots, DamageKind::Fire) => 30,
+            (Gear::IronSword, DamageKind::Bite) => 40,
+            (Gear::Lantern, DamageKind::Bite) => 15,
+            _ => 0,
+        }
+    }
+}
+
+impl Equipment {
+    pub fn create_empty() -> Self {
+        Equipment {
+            helmet: None,
+            chestplate: None,
+            boots: None,
+            weapon: None,
+            light: None,
+        }
+    }
+
+    pub fn get(&self, slot: Slot) -> Option<Gear> {
+        match slot {
+            Slot::Helmet => self.helmet,
+            Slot::Chestplate => self.chestplate,
+            Slot::Boots => self.boots,
+            Slot::Weapon => self.weapon,
+            Slot::Light => self.light,
+        }
+    }
+
+    fn slot_mut(&mut self, slot: Slot) -> &mut Option<Gear> {
+        match slot {
+            Slot::Helmet => &mut self.helmet,
+            Slot::Chestplate => &mut self.chestplate,
+            Slot::Boots => &mut self.boots,
+            Slot::Weapon => &mut self.weapon,
+            Slot::Light => &mut self.light,
+        }
+    }
+
+    // Returns whatever was in the slot before
+    pub fn equip(&mut self, gear: Gear) -> Option<Gear> {
+        self.slot_mut(gear.slot()).replace(gear)
+    }
+
+    pub fn unequip(&mut self, slot: Slot) -> Option<Gear> {
+        self.slot_mut(slot).take()
+    }
+
+    pub fn mitigate(&self, damage: u8, kind: DamageKind) -> u8 {
+        let reduction: u32 = ALL_SLOTS
+            .iter()
+            .filter_map(|&slot| self.get(slot))
+            .map(|gear| gear.reduction(kind))
+            .sum();
+        let reduction = reduction.min(MAX_REDUCTION);
+
+        (damage as u32 * (100 - reduction) / 100) as u8
+    }
+
+    pub fn print(&self) {
+        for (i, slot) in ALL_SLOTS.iter().enumerate() {
+            let string = match self.get(*slot) {
+                Some(gear) => gear.name().to_string(),
+                _ => style::empty(),
+            };
+            println!("{}. {}: {}", i + 1, slot.name(), string);
+        }
+    }
+}
+
+fn parse_equipment_string(equipment_string: &str) -> Result<Equipment, &'static str> {
+    let mut equipment = Equipment::create_empty();
+    // first token is the username
+    for token in equipment_string.split(';').skip(1) {
+        let Some((slot, gear)) = token.split_once(':') else {
+            continue;
+        };
+
+        if let (Some(slot), Some(gear)) = (Slot::from_string(slot), Gear::from_string(gear)) {
+            if gear.slot() == slot {
+                equipment.equip(gear);
+            }
+        }
+    }
+
+    Ok(equipment)
+}
+
+pub fn search_equipment_file(file_path: &str, username: &str) -> Result<Equipment, io::Error> {
+    let file = File::open(file_path)?;
+    let reader = io::BufReader::new(file);
+
+    for line_content in reader.lines().map_while(Result::ok) {
+        if line_content.split(';').next() != Some(username) {
+            continue;
+        }
+
+        if let Ok(equipment) = parse_equipment_string(&line_content) {
+            return Ok(equipment);
+        }
+    }
+
+    Ok(Equipment::create_empty())
+}
+
+pub fn update_equipment_file(players: &[Player], file_path: &str) -> io::Result<()> {
+    let mut file = File::create(file_path)?;
+
+    for player in players {
+        let mut equipment_string = String::new();
+        equipment_string.push_str(&player.account.username);
+        equipment_string.push(';');
+
+        for slot in ALL_SLOTS {
+            if let Some(gear) = player.equipment.get(slot) {
+                equipment_string.push_str(&format!("{}:{};", slot.to_string(), gear.to_string()));
+            }
+        }
+
+        equipment_string.push('\n');
+        file.write_all(equipment_string.as_bytes())?;
+    }
+
+    Ok(())
+}
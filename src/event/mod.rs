@@ -0,0 +1,14 @@
+use crate::inventory::Ore;
+use crate::money::Money;
+
+// Something that happened in the game that other systems might react to
+#[derive(Clone, Copy)]
+pub enum GameEvent {
+    Dug,
+    OreMined(Ore),
+    DepthReached(u8),
+    PickaxeUpgraded(u8),
+    CreeperSurvived,
+    MoneyEarned(Money),
+    FoodEaten,
+}
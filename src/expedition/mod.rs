@@ -0,0 +1,57 @@
+// What happened on the current trip into the mine, from entering it until
+// the player is back on the surface, where it's summed up for them.
+use crate::event::GameEvent;
+use crate::inventory::Ore;
+use crate::lang::{text, tr};
+use crate::mine::ENTRANCE;
+
+pub struct Expedition {
+    // (ore, pieces) in the order they were first found
+    pub ores: Vec<(Ore, u32)>,
+    pub damage_taken: u32,
+    pub deepest: u8,
+}
+
+impl Expedition {
+    pub fn create_empty() -> Self {
+        Expedition {
+            ores: Vec::new(),
+            damage_taken: 0,
+            deepest: ENTRANCE.2,
+        }
+    }
+
+    pub fn record(&mut self, event: GameEvent) {
+        match event {
+            GameEvent::OreMined(ore) => match self.ores.iter_mut().find(|(x, _)| *x == ore) {
+                Some((_, count)) => *count = count.saturating_add(1),
+                None => self.ores.push((ore, 1)),
+            },
+            GameEvent::DepthReached(depth) => self.deepest = self.deepest.max(depth),
+            _ => (),
+        }
+    }
+
+    pub fn add_damage(&mut self, damage: u8) {
+        self.damage_taken = self.damage_taken.saturating_add(damage as u32);
+    }
+
+    // One line per fact, for the log
+    pub fn summary(&self) -> Vec<String> {
+        let ores = match self.ores.is_empty() {
+            true => text("common.none").to_string(),
+            false => self
+                .ores
+                .iter()
+                .map(|(ore, count)| format!("{} {}", count, ore.name()))
+                .collect::<Vec<String>>()
+                .join(", "),
+        };
+        vec![
+            tr!("expedition.summary"),
+            tr!("expedition.deepest", self.deepest),
+            tr!("expedition.ores", ores),
+            tr!("expedition.damage", self.damage_taken),
+        ]
+    }
+}
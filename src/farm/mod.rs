@@ -0,0 +1,251 @@
+// The farm plot every account has on the surface. Seeds are bought when
+// they are planted and grow with the world clock, so crops ripen while the
+// player is off digging. Ripe crops are harvested into the food bag.
+use crate::clock::Clock;
+use crate::inventory::Food;
+use crate::lang::{text, tr};
+use crate::money::Money;
+use crate::player::Player;
+use std::fs::File;
+use std::io::{self, BufRead, Write};
+
+pub const FARM_PLOTS: usize = 4;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Crop {
+    Wheat,
+    Carrot,
+    Apple,
+}
+
+pub const ALL_CROPS: [Crop; 3] = [Crop::Wheat, Crop::Carrot, Crop::Apple];
+
+#[derive(Clone, Copy)]
+pub struct Planting {
+    pub crop: Crop,
+    // clock minutes when it was planted
+    pub planted: u64,
+}
+
+pub struct Farm {
+    pub plots: [Option<Planting>; FARM_PLOTS],
+}
+
+impl Crop {
+    pub fn seed_price(self) -> Money {
+        Money::new(match self {
+            Crop::Wheat => 5,
+            Crop::Carrot => 10,
+            Crop::Apple => 15,
+        })
+    }
+
+    // Game minutes from planting until it can be harvested
+    pub fn growth_minutes(self) -> u64 {
+        match self {
+            Crop::Wheat => 6 * 60,
+            Crop::Carrot => 10 * 60,
+            Crop::Apple => 16 * 60,
+        }
+    }
+
+    pub fn food(self) -> Food {
+        match self {
+            Crop::Wheat => Food::Wheat,
+            Crop::Carrot => Food::Carrot,
+            Crop::Apple => Food::Apple,
+        }
+    }
+
+    // Pieces of food one plot gives
+    pub fn harvest_amount(self) -> u32 {
+        match self {
+            Crop::Wheat | Crop::Carrot => 2,
+            Crop::Apple => 1,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        text(match self {
+            Crop::Wheat => "crop.wheat",
+            Crop::Carrot => "crop.carrot",
+            Crop::Apple => "crop.apple",
+        })
+    }
+
+    pub fn to_string(self) -> &'static str {
+        match self {
+            Crop::Wheat => "Wheat",
+            Crop::Carrot => "Carrot",
+            Crop::Apple => "Apple",
+        }
+    }
+
+    pub fn from_string(string: &str) -> Option<Crop> {
+        ALL_CROPS
+            .into_iter()
+            .find(|crop| crop.to_string().eq_ignore_ascii_case(string))
+    }
+}
+
+impl Planting {
+    // 0 to 100
+    pub fn growth(self, clock: &Clock) -> u64 {
+        let grown = clock.minutes.saturating_sub(self.planted);
+        (grown.saturating_mul(100) / self.crop.growth_minutes()).min(100)
+    }
+
+    pub fn is_ripe(self, clock: &Clock) -> bool {
+        self.growth(clock) == 100
+    }
+
+    // Like `Carrot (40%)`
+    pub fn describe(self, clock: &Clock) -> String {
+        match self.is_ripe(clock) {
+            true => tr!("farm.ripe", self.crop.name()),
+            false => tr!("farm.growing", self.crop.name(), self.growth(clock)),
+        }
+    }
+}
+
+impl Farm {
+    pub fn create_empty() -> Self {
+        Farm {
+            plots: [None; FARM_PLOTS],
+        }
+    }
+
+    pub fn has_free_plot(&self) -> bool {
+        self.plots.iter().any(|x| x.is_none())
+    }
+
+    pub fn has_ripe(&self, clock: &Clock) -> bool {
+        self.plots.iter().flatten().any(|x| x.is_ripe(clock))
+    }
+
+    pub fn print(&self, clock: &Clock) {
+        for (i, plot) in self.plots.iter().enumerate() {
+            let string = match plot {
+                Some(planting) => planting.describe(clock),
+                None => text("farm.empty_plot").to_string(),
+            };
+            println!("{}. {}", i + 1, string);
+        }
+    }
+}
+
+pub fn plant(player: &mut Player, clock: &Clock, crop: Crop, log: &mut Vec<String>) -> bool {
+    let Some(plot) = player.farm.plots.iter().position(|x| x.is_none()) else {
+        log.push(tr!("farm.no_free_plot"));
+        return false;
+    };
+    if let Err(msg) = player.spend(crop.seed_price()) {
+        log.push(msg.to_string());
+        return false;
+    }
+
+    player.farm.plots[plot] = Some(Planting {
+        crop,
+        planted: clock.minutes,
+    });
+    log.push(tr!("farm.planted", crop.name(), crop.seed_price()));
+    true
+}
+
+// Moves every ripe crop that fits into the food bag, the rest stays in the
+// ground. Returns false if nothing was harvested.
+pub fn harvest(player: &mut Player, clock: &Clock, log: &mut Vec<String>) -> bool {
+    let mut harvested = 0;
+    let mut left_in_ground = 0;
+    for slot in &mut player.farm.plots {
+        let Some(planting) = *slot else {
+            continue;
+        };
+        if !planting.is_ripe(clock) {
+            continue;
+        }
+        let food = planting.crop.food();
+        let amount = planting.crop.harvest_amount();
+        if player
+            .inventory
+            .foods
+            .iter()
+            .filter(|x| x.is_none())
+            .count()
+            < amount as usize
+        {
+            log.push(tr!("farm.no_room", planting.crop.name()));
+            left_in_ground += 1;
+            continue;
+        }
+
+        for _ in 0..amount {
+            player.inventory.push_food(food);
+        }
+        *slot = None;
+        harvested += 1;
+        log.push(tr!("farm.harvested", amount, food.name()));
+    }
+
+    if harvested == 0 && left_in_ground == 0 {
+        log.push(tr!("farm.nothing_ripe"));
+    }
+    harvested > 0
+}
+
+// `username;crop@planted;-;...` with `-` for empty plots
+fn parse_farm_string(farm_string: &str) -> Result<Farm, &'static str> {
+    let tokens: Vec<&str> = farm_string.split(';').skip(1).collect();
+    let mut farm = Farm::create_empty();
+    for (slot, token) in farm.plots.iter_mut().zip(tokens) {
+        let Some((crop, planted)) = token.split_once('@') else {
+            continue;
+        };
+        *slot = Some(Planting {
+            crop: Crop::from_string(crop).ok_or("Invalid crop")?,
+            planted: planted.parse().map_err(|_| "Invalid time")?,
+        });
+    }
+    Ok(farm)
+}
+
+pub fn search_farm_file(file_path: &str, username: &str) -> Result<Farm, io::Error> {
+    let file = File::open(file_path)?;
+    let reader = io::BufReader::new(file);
+
+    for line_content in reader.lines().map_while(Result::ok) {
+        if line_content.split(';').next() != Some(username) {
+            continue;
+        }
+
+        if let Ok(farm) = parse_farm_string(&line_content) {
+            return Ok(farm);
+        }
+    }
+
+    Ok(Farm::create_empty())
+}
+
+pub fn update_farm_file(players: &[Player], file_path: &str) -> io::Result<()> {
+    let mut file = File::create(file_path)?;
+
+    for player in players {
+        let mut farm_string = player.account.username.clone();
+        for plot in &player.farm.plots {
+            farm_string.push(';');
+            match plot {
+                Some(planting) => farm_string.push_str(&format!(
+                    "{}@{}",
+                    planting.crop.to_string(),
+                    planting.planted
+                )),
+                None => farm_string.push('-'),
+            }
+        }
+
+        farm_string.push('\n');
+        file.write_all(farm_string.as_bytes())?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,559 @@
+// Game actions shared by every frontend. Instead of printing, they push
+// whatever happened onto a log so each frontend can show it its own way.
+use crate::clock::{self, Clock, COOK_MINUTES, CRAFT_MINUTES, DIG_MINUTES, TRAVEL_MINUTES};
+use crate::consumable::Consumable;
+use crate::effect::EffectKind;
+use crate::equipment::{DamageKind, Gear};
+use crate::event::GameEvent;
+use crate::expedition::Expedition;
+use crate::inventory::{Food, Item, Ore};
+use crate::lang::{text, tr};
+use crate::market::Market;
+use crate::mine::{Direction, Tile};
+use crate::money::Money;
+use crate::player::Player;
+use crate::quest::{Objective, QuestUpdate, Reward};
+use rand::Rng;
+
+// Chance in percent that a creature is waiting in plain rock at night
+const NIGHT_SPAWN_PERCENT: u32 = 15;
+// Chance in percent, for every hour that passes, that a piece of raw meat
+// in the food bag goes off
+const SPOIL_PERCENT: u32 = 5;
+const FOOD_POISONING_TURNS: u8 = 5;
+const HEALING_POTION_HEAL: u8 = 50;
+const POTION_TURNS: u8 = 10;
+const TORCH_TURNS: u8 = 15;
+// Chance in percent that a lit torch shows a hazard before it's dug into
+const TORCH_SPOT_PERCENT: u32 = 50;
+// Levels a stick of TNT blasts through, and the chance it hurts the player
+const TNT_LEVELS: u8 = 3;
+const TNT_BACKFIRE_PERCENT: u32 = 30;
+const TNT_DAMAGE: u8 = 25;
+// Every level on the way back up takes a while and may go wrong, a lit
+// torch halves the chance
+const CLIMB_MINUTES: u64 = 10;
+const CLIMB_HAZARD_PERCENT: u32 = 10;
+const FALLING_ROCK_DAMAGE: u8 = 8;
+
+pub fn record_event(player: &mut Player, event: GameEvent, log: &mut Vec<String>) {
+    player.expedition.record(event);
+    for achievement in player.achievements.record(event) {
+        log.push(tr!(
+            "game.achievement_unlocked",
+            text(achievement.name),
+            text(achievement.description)
+        ));
+    }
+
+    for update in player.quests.record(event) {
+        match update {
+            QuestUpdate::Completed(quest) => {
+                log.push(tr!("game.quest_complete", quest.describe()));
+                grant_reward(player, quest.reward, log);
+            }
+            QuestUpdate::Failed(quest) => log.push(tr!("game.quest_failed", quest.describe())),
+        }
+    }
+}
+
+pub fn grant_reward(player: &mut Player, reward: Reward, log: &mut Vec<String>) {
+    match reward {
+        Reward::Money(amount) => {
+            player.account.money += amount;
+            log.push(tr!("game.received_money", amount));
+            record_event(player, GameEvent::MoneyEarned(amount), log);
+        }
+        Reward::Food(food) => {
+            if player.inventory.foods.iter().any(|x| x.is_none()) {
+                player.inventory.push_food(food);
+                log.push(tr!("game.received_food", food.name()));
+            } else {
+                log.push(tr!("game.food_lost", food.name()));
+            }
+        }
+    }
+}
+
+// Lets time pass, telling the player when night falls or the day breaks.
+// The player's raw meat may spoil on every hour that goes by.
+pub fn pass_time(player: &mut Player, clock: &mut Clock, minutes: u64, log: &mut Vec<String>) {
+    let was_night = clock.is_night();
+    let hour = clock.minutes / 60;
+    clock.advance(minutes);
+    match (was_night, clock.is_night()) {
+        (false, true) => log.push(tr!("clock.night_falls")),
+        (true, false) => log.push(tr!("clock.day_breaks")),
+        _ => (),
+    }
+
+    let mut rng = rand::thread_rng();
+    for _ in hour..clock.minutes / 60 {
+        for slot in player.inventory.foods.iter_mut() {
+            let Some(food) = *slot else {
+                continue;
+            };
+            if food.spoils() && rng.gen_range(1..=100) <= SPOIL_PERCENT {
+                *slot = Some(Food::RottenFlesh);
+                log.push(tr!("game.food_spoiled", food.name()));
+            }
+        }
+    }
+}
+
+// Players who stopped playing in the middle of a trip carry on from where
+// they were, the way out is always the climb
+pub fn enter_mine(player: &mut Player, clock: &mut Clock, log: &mut Vec<String>) {
+    if player.is_in_mine() {
+        log.push(tr!("game.still_in_mine", player.get_depth()));
+        return;
+    }
+    player.expedition = Expedition::create_empty();
+    pass_time(player, clock, TRAVEL_MINUTES, log);
+}
+
+// Climbs back up one level at a time, then walks to town
+pub fn leave_mine(player: &mut Player, clock: &mut Clock, log: &mut Vec<String>) {
+    let levels = player.get_depth().saturating_sub(1);
+    let was_alive = player.is_alive();
+    if levels > 0 && was_alive {
+        log.push(tr!("game.climbing", levels));
+    }
+    for _ in 0..levels {
+        // being carried out takes as long as the climb
+        if !player.is_alive() {
+            pass_time(player, clock, CLIMB_MINUTES, log);
+            continue;
+        }
+        climb_level(player, clock, log);
+    }
+
+    // someone finds them and carries them out, but their ores stay behind
+    if !player.is_alive() {
+        log.push(match was_alive {
+            true => tr!("game.passed_out"),
+            false => tr!("game.carried_out"),
+        });
+        player.purge_inventory();
+    }
+    surface(player, clock, log);
+}
+
+fn climb_level(player: &mut Player, clock: &mut Clock, log: &mut Vec<String>) {
+    pass_time(player, clock, CLIMB_MINUTES, log);
+    let tick = player.tick_effects();
+    if tick.damage > 0 {
+        log.push(tr!("game.effect_damage", tick.damage));
+    }
+    for effect in tick.expired {
+        log.push(tr!("game.effect_expired", effect.name()));
+    }
+
+    let mut chance = CLIMB_HAZARD_PERCENT;
+    if player.effects.has(EffectKind::Torchlight) {
+        chance /= 2;
+    }
+    let mut rng = rand::thread_rng();
+    if rng.gen_range(1..=100) > chance {
+        return;
+    }
+    match rng.gen() {
+        true => {
+            let damage = player.take_hit(FALLING_ROCK_DAMAGE, DamageKind::Explosion);
+            log.push(tr!("game.falling_rocks", damage));
+        }
+        false => {
+            log.push(tr!("game.spider"));
+            player.take_hit(5, DamageKind::Bite);
+            player.effects.apply(EffectKind::Poison, 4);
+        }
+    }
+}
+
+// Back in town, with a look back at how the trip went
+fn surface(player: &mut Player, clock: &mut Clock, log: &mut Vec<String>) {
+    player.stop_mining();
+    pass_time(player, clock, TRAVEL_MINUTES, log);
+    log.extend(player.expedition.summary());
+    player.expedition = Expedition::create_empty();
+}
+
+// Returns false if the shop was already open
+pub fn wait_for_shop(player: &mut Player, clock: &mut Clock, log: &mut Vec<String>) -> bool {
+    let minutes = clock.until_shop_opens();
+    if minutes == 0 {
+        log.push(tr!("clock.shop_already_open"));
+        return false;
+    }
+    pass_time(player, clock, minutes, log);
+    log.push(tr!("clock.waited", clock.describe()));
+    true
+}
+
+// Pushes why not onto the log when the shop is closed
+pub fn shop_is_open(clock: &Clock, log: &mut Vec<String>) -> bool {
+    if !clock.is_shop_open() {
+        log.push(clock::shop_closed_message());
+        return false;
+    }
+    true
+}
+
+// Returns false if no turn was taken, when the player has no health left or
+// bedrock is in the way
+pub fn dig(
+    player: &mut Player,
+    clock: &mut Clock,
+    direction: Direction,
+    log: &mut Vec<String>,
+) -> bool {
+    if !player.is_alive() {
+        log.push(tr!("game.no_health"));
+        return false;
+    }
+
+    if !player.mine.can_move(direction) {
+        log.push(tr!("game.bedrock"));
+        return false;
+    }
+
+    record_event(player, GameEvent::Dug, log);
+    pass_time(player, clock, DIG_MINUTES, log);
+
+    if player.burn_hunger() {
+        log.push(tr!("game.starving"));
+    }
+
+    let tick = player.tick_effects();
+    if tick.damage > 0 {
+        log.push(tr!("game.effect_damage", tick.damage));
+    }
+    if tick.heal > 0 {
+        log.push(tr!("game.effect_heal", tick.heal));
+    }
+    for effect in tick.expired {
+        log.push(tr!("game.effect_expired", effect.name()));
+    }
+
+    let previous_depth = player.get_depth();
+    let mut rng = rand::thread_rng();
+    let night_spawn = clock.is_night()
+        && !player.effects.has(EffectKind::NightVision)
+        && rng.gen_range(1..=100) <= NIGHT_SPAWN_PERCENT;
+    let spotted =
+        player.effects.has(EffectKind::Torchlight) && rng.gen_range(1..=100) <= TORCH_SPOT_PERCENT;
+    let tile = match player.mine.step(direction) {
+        Some(Tile::Rock) if night_spawn => match rand::random() {
+            true => Some(Tile::Creeper),
+            false => Some(Tile::Spider),
+        },
+        Some(Tile::Creeper | Tile::Spider | Tile::Lava) if spotted => {
+            log.push(tr!("game.torch_spotted"));
+            Some(Tile::Rock)
+        }
+        tile => tile,
+    };
+    match tile {
+        None => log.push(tr!("game.old_tunnel")),
+        Some(Tile::Rock) | Some(Tile::Bedrock) => log.push(tr!("game.dug_rock")),
+        Some(Tile::Ore(ore)) => collect_ore(player, ore, log),
+        Some(Tile::Creeper) => {
+            log.push(tr!("game.creeper"));
+            player.take_hit(30, DamageKind::Explosion);
+            if player.is_alive() {
+                record_event(player, GameEvent::CreeperSurvived, log);
+            }
+        }
+        Some(Tile::Spider) => {
+            log.push(tr!("game.spider"));
+            player.take_hit(5, DamageKind::Bite);
+            player.effects.apply(EffectKind::Poison, 4);
+        }
+        Some(Tile::Lava) if player.effects.has(EffectKind::FireResistance) => {
+            log.push(tr!("game.lava_resisted"));
+        }
+        Some(Tile::Lava) => {
+            log.push(tr!("game.lava"));
+            player.take_hit(10, DamageKind::Fire);
+            player.effects.apply(EffectKind::Burning, 3);
+        }
+        Some(Tile::Water) => {
+            log.push(tr!("game.water"));
+            if player.effects.remove(EffectKind::Burning) {
+                log.push(tr!("game.fire_out"));
+            }
+        }
+    }
+
+    if player.get_depth() > previous_depth {
+        log.push(tr!("game.dug_deeper"));
+        record_event(player, GameEvent::DepthReached(player.get_depth()), log);
+    }
+    true
+}
+
+// Only what fits in the ore bag counts as mined, the rest stays behind
+fn collect_ore(player: &mut Player, ore: Ore, log: &mut Vec<String>) {
+    let (mined, lost) = player.mine_ore(ore);
+    if !mined.is_empty() {
+        log.push(tr!("game.mined", mined.len(), ore.name()));
+    }
+    if lost > 0 {
+        log.push(tr!("game.ore_bag_full", lost, ore.name()));
+    }
+    for ore in mined {
+        record_event(player, GameEvent::OreMined(ore), log);
+    }
+}
+
+// Returns false if there was nothing to eat at that index
+pub fn eat(player: &mut Player, index: i32, log: &mut Vec<String>) -> bool {
+    let Some(food) = player.eat(index) else {
+        log.push(tr!("game.invalid_food"));
+        return false;
+    };
+
+    log.push(tr!("game.ate"));
+    if rand::thread_rng().gen_range(1..=100) <= food.poison_chance() {
+        player
+            .effects
+            .apply(EffectKind::Poison, FOOD_POISONING_TURNS);
+        log.push(tr!("game.food_poisoning", food.name()));
+    }
+    record_event(player, GameEvent::FoodEaten, log);
+    true
+}
+
+// Cooks the raw food at that index of the food bag over a piece of coal.
+// Returns false if it couldn't be cooked.
+pub fn cook(player: &mut Player, clock: &mut Clock, index: i32, log: &mut Vec<String>) -> bool {
+    let food = match player.can_eat(index) {
+        true => player.inventory.foods[index as usize - 1],
+        false => None,
+    };
+    let Some((raw, cooked)) = food.and_then(|raw| Some((raw, raw.cooked()?))) else {
+        log.push(tr!("game.cannot_cook"));
+        return false;
+    };
+    if player.inventory.count_ore(Ore::Coal) == 0 {
+        log.push(tr!("game.no_fuel", Ore::Coal.name()));
+        return false;
+    }
+
+    player.inventory.remove_ore(Ore::Coal, 1);
+    player.inventory.foods[index as usize - 1] = Some(cooked);
+    log.push(tr!("game.cooked", raw.name(), cooked.name()));
+    pass_time(player, clock, COOK_MINUTES, log);
+    true
+}
+
+// Returns how much money the ores were sold for, at the market's prices
+pub fn sell_all_ores(player: &mut Player, market: &mut Market, log: &mut Vec<String>) -> Money {
+    let total_added_money: Money = player
+        .inventory
+        .ores
+        .iter()
+        .flatten()
+        .map(|&ore| market.sell(ore))
+        .sum();
+
+    player.account.money += total_added_money;
+    player.purge_inventory();
+    if !total_added_money.is_zero() {
+        log.push(tr!("game.sold_ores", total_added_money));
+        record_event(player, GameEvent::MoneyEarned(total_added_money), log);
+    } else {
+        log.push(tr!("game.no_ores"));
+    }
+    total_added_money
+}
+
+pub fn buy_food(player: &mut Player, food: Food, log: &mut Vec<String>) -> bool {
+    if !player.inventory.foods.iter().any(|x| x.is_none()) {
+        log.push(tr!("game.no_space"));
+        return false;
+    }
+
+    if let Err(msg) = player.spend(food.price()) {
+        log.push(msg.to_string());
+        return false;
+    }
+
+    log.push(tr!("game.buying", food.name(), food.price()));
+    player.inventory.push_food(food);
+    true
+}
+
+pub fn buy_consumable(player: &mut Player, consumable: Consumable, log: &mut Vec<String>) -> bool {
+    if !player.inventory.consumables.iter().any(|x| x.is_none()) {
+        log.push(tr!("game.no_space"));
+        return false;
+    }
+
+    if let Err(msg) = player.spend(consumable.price()) {
+        log.push(msg.to_string());
+        return false;
+    }
+
+    log.push(tr!("game.buying", consumable.name(), consumable.price()));
+    player.inventory.push_consumable(consumable);
+    true
+}
+
+// Uses one out of the bag while in the mine.
+// Returns false if the player had none or it couldn't be used right now.
+pub fn use_consumable(
+    player: &mut Player,
+    clock: &mut Clock,
+    consumable: Consumable,
+    log: &mut Vec<String>,
+) -> bool {
+    if player.inventory.count_item(Item::Consumable(consumable)) == 0 {
+        log.push(tr!("game.none_to_use", consumable.name()));
+        return false;
+    }
+    if consumable.mine_only() && !player.is_in_mine() {
+        log.push(tr!("game.only_in_mine", consumable.name()));
+        return false;
+    }
+    // no climbing or blasting your way out with no health left
+    if matches!(consumable, Consumable::Rope | Consumable::Tnt) && !player.is_alive() {
+        log.push(tr!("game.no_health"));
+        return false;
+    }
+    if consumable == Consumable::Tnt && !player.mine.can_move(Direction::Down) {
+        log.push(tr!("game.bedrock"));
+        return false;
+    }
+
+    player.inventory.remove_item(Item::Consumable(consumable));
+    log.push(tr!("game.used", consumable.name()));
+    match consumable {
+        Consumable::HealingPotion => {
+            player.heal(HEALING_POTION_HEAL);
+            log.push(tr!("game.healed", HEALING_POTION_HEAL));
+        }
+        Consumable::NightVisionPotion => {
+            player.effects.apply(EffectKind::NightVision, POTION_TURNS);
+        }
+        Consumable::FireResistancePotion => {
+            player
+                .effects
+                .apply(EffectKind::FireResistance, POTION_TURNS);
+            if player.effects.remove(EffectKind::Burning) {
+                log.push(tr!("game.potion_fire_out"));
+            }
+        }
+        Consumable::Torch => player.effects.apply(EffectKind::Torchlight, TORCH_TURNS),
+        Consumable::Tnt => blast(player, clock, log),
+        Consumable::Rope => {
+            log.push(tr!("game.climbed_rope"));
+            surface(player, clock, log);
+        }
+    }
+    true
+}
+
+// Blows a hole through the next few levels, collecting the ores in it
+fn blast(player: &mut Player, clock: &mut Clock, log: &mut Vec<String>) {
+    let mut levels = 0;
+    while levels < TNT_LEVELS && player.mine.can_move(Direction::Down) {
+        if let Some(Tile::Ore(ore)) = player.mine.step(Direction::Down) {
+            collect_ore(player, ore, log);
+        }
+        levels += 1;
+    }
+    log.push(tr!("game.blasted", levels));
+    pass_time(player, clock, DIG_MINUTES, log);
+
+    if rand::thread_rng().gen_range(1..=100) <= TNT_BACKFIRE_PERCENT {
+        let damage = player.take_hit(TNT_DAMAGE, DamageKind::Explosion);
+        log.push(tr!("game.tnt_backfired", damage));
+    }
+    record_event(player, GameEvent::DepthReached(player.get_depth()), log);
+}
+
+pub fn upgrade_pickaxe(player: &mut Player, log: &mut Vec<String>) -> bool {
+    if player.is_pickaxe_maxed() {
+        log.push(tr!("game.pickaxe_maxed"));
+        return false;
+    }
+
+    if let Err(msg) = player.spend(player.upgrade_pickaxe_cost()) {
+        log.push(msg.to_string());
+        return false;
+    }
+
+    player.upgrade_pickaxe();
+    log.push(tr!("game.pickaxe_upgraded"));
+    record_event(
+        player,
+        GameEvent::PickaxeUpgraded(player.account.pickaxe_level),
+        log,
+    );
+    true
+}
+
+pub fn buy_gear(player: &mut Player, gear: Gear, log: &mut Vec<String>) -> bool {
+    if !player.inventory.gear.iter().any(|x| x.is_none()) {
+        log.push(tr!("game.no_space"));
+        return false;
+    }
+
+    if let Err(msg) = player.spend(gear.price()) {
+        log.push(msg.to_string());
+        return false;
+    }
+
+    log.push(tr!("game.buying", gear.name(), gear.price()));
+    player.inventory.push_gear(gear);
+    true
+}
+
+pub fn craft_gear(
+    player: &mut Player,
+    clock: &mut Clock,
+    gear: Gear,
+    log: &mut Vec<String>,
+) -> bool {
+    if !player.inventory.gear.iter().any(|x| x.is_none()) {
+        log.push(tr!("game.no_space"));
+        return false;
+    }
+
+    let (ore, amount) = gear.recipe();
+    if player.inventory.count_ore(ore) < amount {
+        log.push(tr!("game.not_enough_ore", ore.name()));
+        return false;
+    }
+
+    player.inventory.remove_ore(ore, amount);
+    pass_time(player, clock, CRAFT_MINUTES, log);
+    log.push(tr!("game.crafted", gear.name()));
+    player.inventory.push_gear(gear);
+    true
+}
+
+pub fn turn_in_quests(player: &mut Player, log: &mut Vec<String>) {
+    let mut delivered = Vec::new();
+    let mut remaining = Vec::new();
+    for quest in player.quests.active.drain(..) {
+        if quest.can_deliver(&player.inventory) {
+            delivered.push(quest);
+        } else {
+            remaining.push(quest);
+        }
+    }
+    player.quests.active = remaining;
+
+    if delivered.is_empty() {
+        log.push(tr!("game.nothing_to_deliver"));
+    }
+    for quest in delivered {
+        if let Objective::Deliver { ore, amount, .. } = quest.objective {
+            player.inventory.remove_ore(ore, amount);
+        }
+        log.push(tr!("game.quest_complete", quest.describe()));
+        grant_reward(player, quest.reward, log);
+    }
+}
@@ -0,0 +1,78 @@
+// Every menu reads the player's input through here, so running out of
+// input or pressing Ctrl-C ends the game the same way everywhere.
+use crate::lang::text;
+use crate::style;
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+// Why the game has to stop
+pub enum Exit {
+    Save,
+    Discard,
+}
+
+pub fn install_interrupt_handler() {
+    // stdin can't be interrupted, so the next line the player enters is
+    // treated as the answer to this prompt
+    let result = ctrlc::set_handler(|| {
+        INTERRUPTED.store(true, Ordering::SeqCst);
+        print!("\n{}", text("input.quit_prompt"));
+        unsafe_stdout_flush();
+    });
+    if result.is_err() {
+        println!("{}", text("input.no_interrupt_handler"));
+    }
+}
+
+pub fn unsafe_stdout_flush() {
+    io::stdout().flush().unwrap();
+}
+
+pub fn read_line() -> Result<String, Exit> {
+    let mut input = String::new();
+    match io::stdin().read_line(&mut input) {
+        // end of input (Ctrl-D), nothing more is coming
+        Ok(0) | Err(_) => {
+            println!();
+            return Err(Exit::Save);
+        }
+        Ok(_) => (),
+    }
+
+    if INTERRUPTED.swap(false, Ordering::SeqCst) {
+        return match input.trim().to_lowercase().as_str() {
+            "c" => {
+                print!(">> ");
+                unsafe_stdout_flush();
+                read_line()
+            }
+            "n" => Err(Exit::Discard),
+            _ => Err(Exit::Save),
+        };
+    }
+
+    Ok(input.trim().to_string())
+}
+
+// Asked when the player types the quit command, Ok means keep playing
+pub fn ask_quit() -> Result<(), Exit> {
+    print!("{}", text("input.quit_prompt"));
+    unsafe_stdout_flush();
+    match read_line()?.to_lowercase().as_str() {
+        "c" => Ok(()),
+        "n" => Err(Exit::Discard),
+        _ => Err(Exit::Save),
+    }
+}
+
+pub fn wait_for_enter() -> Result<(), Exit> {
+    println!("{}", text("input.press_enter"));
+    read_line()?;
+    Ok(())
+}
+
+pub fn invalid_choice() {
+    println!("{}", style::error(text("input.invalid_choice")));
+}
@@ -1,31 +1,82 @@
+use crate::consumable::{Consumable, ALL_CONSUMABLES};
+use crate::effect::EffectKind;
+use crate::equipment::{Gear, ALL_GEAR};
+use crate::lang::text;
+use crate::money::Money;
 use crate::player::*;
+use crate::style;
 use std::fs::File;
 use std::io::{self, BufRead, Write};
 
 pub struct Inventory {
     pub ores: [Option<Ore>; 20],
     pub foods: [Option<Food>; 6],
+    pub gear: [Option<Gear>; 6],
+    pub consumables: [Option<Consumable>; 6],
 }
 
-#[derive(Clone, Copy)]
+#[allow(clippy::enum_variant_names)]
+#[derive(Clone, Copy, PartialEq)]
 pub enum Ore {
+    // burnt as fuel for cooking
+    Coal,
     IronOre,
     GoldOre,
     Diamond,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq)]
 pub enum Food {
     Apple,
     Chicken,
     Beef,
+    // only grown on the farm
+    Wheat,
+    Carrot,
+    CookedChicken,
+    CookedBeef,
+    // what raw meat turns into when it is kept too long
+    RottenFlesh,
 }
 
+// How rare an item is, the line menus color names by it
+#[derive(Clone, Copy, PartialEq)]
+pub enum Rarity {
+    Common,
+    Uncommon,
+    Rare,
+}
+
+// Anything that fits in the inventory, for moving goods between accounts
+#[derive(Clone, Copy, PartialEq)]
+pub enum Item {
+    Ore(Ore),
+    Food(Food),
+    Gear(Gear),
+    Consumable(Consumable),
+}
+
+pub const ALL_FOODS: [Food; 8] = [
+    Food::Apple,
+    Food::Chicken,
+    Food::Beef,
+    Food::Wheat,
+    Food::Carrot,
+    Food::CookedChicken,
+    Food::CookedBeef,
+    Food::RottenFlesh,
+];
+// What the shop sells, the rest comes from the farm
+pub const SHOP_FOODS: [Food; 3] = [Food::Apple, Food::Chicken, Food::Beef];
+pub const ALL_ORES: [Ore; 4] = [Ore::Coal, Ore::IronOre, Ore::GoldOre, Ore::Diamond];
+
 impl Inventory {
     pub fn create_empty() -> Self {
         Inventory {
             ores: [None; 20],
             foods: [None; 6],
+            gear: [None; 6],
+            consumables: [None; 6],
         }
     }
 
@@ -38,20 +89,143 @@ impl Inventory {
         }
     }
 
-    pub fn push_ore(&mut self, ore: Ore) {
-        for i in 0..self.ores.len() {
-            if self.ores[i].is_none() {
-                self.ores[i] = Some(ore);
+    // Returns false if there was no room for it
+    pub fn push_gear(&mut self, gear: Gear) -> bool {
+        match self.gear.iter_mut().find(|x| x.is_none()) {
+            Some(slot) => {
+                *slot = Some(gear);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Returns false if there was no room for it
+    pub fn push_consumable(&mut self, consumable: Consumable) -> bool {
+        match self.consumables.iter_mut().find(|x| x.is_none()) {
+            Some(slot) => {
+                *slot = Some(consumable);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Returns false if there was no room for it
+    pub fn push_ore(&mut self, ore: Ore) -> bool {
+        match self.ores.iter_mut().find(|x| x.is_none()) {
+            Some(slot) => {
+                *slot = Some(ore);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn count_ore(&self, ore: Ore) -> u32 {
+        self.ores.iter().flatten().filter(|&&x| x == ore).count() as u32
+    }
+
+    pub fn remove_ore(&mut self, ore: Ore, amount: u32) {
+        let mut removed = 0;
+        for slot in &mut self.ores {
+            if removed == amount {
                 break;
             }
+            if *slot == Some(ore) {
+                *slot = None;
+                removed += 1;
+            }
+        }
+    }
+
+    pub fn count_item(&self, item: Item) -> u32 {
+        match item {
+            Item::Ore(ore) => self.count_ore(ore),
+            Item::Food(food) => self.foods.iter().flatten().filter(|&&x| x == food).count() as u32,
+            Item::Gear(gear) => self.gear.iter().flatten().filter(|&&x| x == gear).count() as u32,
+            Item::Consumable(consumable) => {
+                let slots = self.consumables.iter().flatten();
+                slots.filter(|&&x| x == consumable).count() as u32
+            }
+        }
+    }
+
+    // Free slots in the bag the item goes in
+    pub fn free_space(&self, item: Item) -> u32 {
+        let free = match item {
+            Item::Ore(_) => self.ores.iter().filter(|x| x.is_none()).count(),
+            Item::Food(_) => self.foods.iter().filter(|x| x.is_none()).count(),
+            Item::Gear(_) => self.gear.iter().filter(|x| x.is_none()).count(),
+            Item::Consumable(_) => self.consumables.iter().filter(|x| x.is_none()).count(),
+        };
+        free as u32
+    }
+
+    // Returns false if there was no room for it
+    pub fn push_item(&mut self, item: Item) -> bool {
+        if self.free_space(item) == 0 {
+            return false;
+        }
+        match item {
+            Item::Ore(ore) => {
+                self.push_ore(ore);
+            }
+            Item::Food(food) => self.push_food(food),
+            Item::Gear(gear) => {
+                self.push_gear(gear);
+            }
+            Item::Consumable(consumable) => {
+                self.push_consumable(consumable);
+            }
+        }
+        true
+    }
+
+    // Returns false if there wasn't one to remove
+    pub fn remove_item(&mut self, item: Item) -> bool {
+        fn take<T: PartialEq>(slots: &mut [Option<T>], wanted: T) -> bool {
+            let wanted = Some(wanted);
+            slots
+                .iter_mut()
+                .find(|x| **x == wanted)
+                .map(|x| x.take())
+                .is_some()
+        }
+
+        match item {
+            Item::Ore(ore) => take(&mut self.ores, ore),
+            Item::Food(food) => take(&mut self.foods, food),
+            Item::Gear(gear) => take(&mut self.gear, gear),
+            Item::Consumable(consumable) => take(&mut self.consumables, consumable),
         }
     }
 
     pub fn print_ores(&self) {
         for (i, ores) in self.ores.iter().enumerate() {
             let string = match ores {
-                Some(ore) => ore.to_string(),
-                _ => "Empty",
+                Some(ore) => style::item(ore.name(), ore.rarity()),
+                _ => style::empty(),
+            };
+            println!("{}. {}", i + 1, string);
+        }
+    }
+
+    pub fn print_gear(&self) {
+        for (i, gear) in self.gear.iter().enumerate() {
+            let string = match gear {
+                Some(gear) => gear.name().to_string(),
+                _ => style::empty(),
+            };
+            println!("{}. {}", i + 1, string);
+        }
+    }
+
+    pub fn print_consumables(&self) {
+        for (i, consumable) in self.consumables.iter().enumerate() {
+            let string = match consumable {
+                Some(consumable) => consumable.name().to_string(),
+                _ => style::empty(),
             };
             println!("{}. {}", i + 1, string);
         }
@@ -60,8 +234,8 @@ impl Inventory {
     pub fn print_food(&self) {
         for (i, food) in self.foods.iter().enumerate() {
             let string = match food {
-                Some(food) => food.to_string(),
-                _ => "Empty",
+                Some(food) => food.name().to_string(),
+                _ => style::empty(),
             };
             println!("{}. {}", i + 1, string);
         }
@@ -76,6 +250,10 @@ fn parse_inventory_string(inventory_string: &str) -> Result<Inventory, &'static
             inventory.push_food(food);
         } else if let Some(ore) = Ore::from_string(token) {
             inventory.push_ore(ore);
+        } else if let Some(gear) = Gear::from_string(token) {
+            inventory.push_gear(gear);
+        } else if let Some(consumable) = Consumable::from_string(token) {
+            inventory.push_consumable(consumable);
         }
     }
 
@@ -86,51 +264,218 @@ pub fn search_inventory_file(file_path: &str, username: &str) -> Result<Inventor
     let file = File::open(file_path)?;
     let reader = io::BufReader::new(file);
 
-    for line in reader.lines() {
-        if let Ok(line_content) = line {
-            if !line_content.starts_with(username) {
-                continue;
-            }
+    for line_content in reader.lines().map_while(Result::ok) {
+        if !line_content.starts_with(username) {
+            continue;
+        }
 
-            if let Ok(inventory) = parse_inventory_string(&line_content) {
-                return Ok(inventory);
-            }
+        if let Ok(inventory) = parse_inventory_string(&line_content) {
+            return Ok(inventory);
         }
     }
 
     Ok(Inventory::create_empty())
 }
 
+impl Item {
+    pub fn all() -> Vec<Item> {
+        let ores = ALL_ORES.iter().map(|&ore| Item::Ore(ore));
+        let foods = ALL_FOODS.iter().map(|&food| Item::Food(food));
+        let gear = ALL_GEAR.iter().map(|&gear| Item::Gear(gear));
+        let consumables = ALL_CONSUMABLES.iter().map(|&x| Item::Consumable(x));
+        ores.chain(foods).chain(gear).chain(consumables).collect()
+    }
+
+    // What the shop pays or asks for it, used as a guide for players
+    pub fn price(self) -> Money {
+        match self {
+            Item::Ore(ore) => ore.price(),
+            Item::Food(food) => food.price(),
+            Item::Gear(gear) => gear.price(),
+            Item::Consumable(consumable) => consumable.price(),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Item::Ore(ore) => ore.name(),
+            Item::Food(food) => food.name(),
+            Item::Gear(gear) => gear.name(),
+            Item::Consumable(consumable) => consumable.name(),
+        }
+    }
+
+    pub fn to_string(self) -> &'static str {
+        match self {
+            Item::Ore(ore) => ore.to_string(),
+            Item::Food(food) => food.to_string(),
+            Item::Gear(gear) => gear.to_string(),
+            Item::Consumable(consumable) => consumable.to_string(),
+        }
+    }
+
+    pub fn from_string(string: &str) -> Option<Item> {
+        Item::all()
+            .into_iter()
+            .find(|item| item.to_string().eq_ignore_ascii_case(string))
+    }
+}
+
 impl Food {
-    pub fn to_string(&self) -> &str {
+    pub fn price(self) -> Money {
+        Money::new(match self {
+            Food::Apple => 30,
+            Food::Chicken => 70,
+            Food::Beef => 90,
+            Food::Wheat => 15,
+            Food::Carrot => 25,
+            Food::CookedChicken => 90,
+            Food::CookedBeef => 110,
+            Food::RottenFlesh => 1,
+        })
+    }
+
+    pub fn heal_amount(self) -> u8 {
+        match self {
+            Food::Apple => 10,
+            Food::Chicken => 30,
+            Food::Beef => 40,
+            Food::Wheat => 5,
+            Food::Carrot => 15,
+            Food::CookedChicken => 50,
+            Food::CookedBeef => 65,
+            Food::RottenFlesh => 0,
+        }
+    }
+
+    pub fn effect(self) -> Option<(EffectKind, u8)> {
+        match self {
+            Food::Apple => Some((EffectKind::Regeneration, 3)),
+            Food::Chicken | Food::Wheat | Food::CookedChicken | Food::RottenFlesh => None,
+            Food::Beef | Food::CookedBeef => Some((EffectKind::Haste, 4)),
+            Food::Carrot => Some((EffectKind::Regeneration, 2)),
+        }
+    }
+
+    // Chance in percent of food poisoning from eating it
+    pub fn poison_chance(self) -> u32 {
+        match self {
+            Food::Chicken => 30,
+            Food::Beef => 15,
+            Food::RottenFlesh => 80,
+            _ => 0,
+        }
+    }
+
+    // What it turns into on the fire, None if it can't be cooked
+    pub fn cooked(self) -> Option<Food> {
+        match self {
+            Food::Chicken => Some(Food::CookedChicken),
+            Food::Beef => Some(Food::CookedBeef),
+            _ => None,
+        }
+    }
+
+    // Only raw meat goes off
+    pub fn spoils(self) -> bool {
+        self.cooked().is_some()
+    }
+
+    // How much hunger is restored by eating this
+    pub fn saturation(self) -> u8 {
+        match self {
+            Food::Apple => 15,
+            Food::Chicken => 35,
+            Food::Beef => 50,
+            Food::Wheat => 20,
+            Food::Carrot => 25,
+            Food::CookedChicken => 45,
+            Food::CookedBeef => 60,
+            Food::RottenFlesh => 5,
+        }
+    }
+
+    // Name shown to the player, to_string is what gets saved
+    pub fn name(self) -> &'static str {
+        text(match self {
+            Food::Apple => "food.apple",
+            Food::Chicken => "food.chicken",
+            Food::Beef => "food.beef",
+            Food::Wheat => "food.wheat",
+            Food::Carrot => "food.carrot",
+            Food::CookedChicken => "food.cooked_chicken",
+            Food::CookedBeef => "food.cooked_beef",
+            Food::RottenFlesh => "food.rotten_flesh",
+        })
+    }
+
+    pub fn to_string(self) -> &'static str {
         match self {
             Food::Apple => "Apple",
             Food::Chicken => "Chicken",
             Food::Beef => "Beef",
+            Food::Wheat => "Wheat",
+            Food::Carrot => "Carrot",
+            Food::CookedChicken => "Cooked Chicken",
+            Food::CookedBeef => "Cooked Beef",
+            Food::RottenFlesh => "Rotten Flesh",
         }
     }
 
-    fn from_string(string: &str) -> Option<Food> {
+    pub fn from_string(string: &str) -> Option<Food> {
         match string {
             "Apple" => Some(Food::Apple),
             "Chicken" => Some(Food::Chicken),
             "Beef" => Some(Food::Beef),
+            "Wheat" => Some(Food::Wheat),
+            "Carrot" => Some(Food::Carrot),
+            "Cooked Chicken" => Some(Food::CookedChicken),
+            "Cooked Beef" => Some(Food::CookedBeef),
+            "Rotten Flesh" => Some(Food::RottenFlesh),
             _ => None,
         }
     }
 }
 
 impl Ore {
-    fn to_string(&self) -> &str {
+    pub fn price(self) -> Money {
+        Money::new(match self {
+            Ore::Coal => 5,
+            Ore::IronOre => 20,
+            Ore::GoldOre => 50,
+            Ore::Diamond => 120,
+        })
+    }
+
+    pub fn rarity(self) -> Rarity {
         match self {
+            Ore::Coal | Ore::IronOre => Rarity::Common,
+            Ore::GoldOre => Rarity::Uncommon,
+            Ore::Diamond => Rarity::Rare,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        text(match self {
+            Ore::Coal => "ore.coal",
+            Ore::IronOre => "ore.iron_ore",
+            Ore::GoldOre => "ore.gold_ore",
+            Ore::Diamond => "ore.diamond",
+        })
+    }
+
+    pub fn to_string(self) -> &'static str {
+        match self {
+            Ore::Coal => "Coal",
             Ore::IronOre => "Iron Ore",
             Ore::GoldOre => "Gold Ore",
             Ore::Diamond => "Diamond",
         }
     }
 
-    fn from_string(string: &str) -> Option<Ore> {
+    pub fn from_string(string: &str) -> Option<Ore> {
         match string {
+            "Coal" => Some(Ore::Coal),
             "Iron Ore" => Some(Ore::IronOre),
             "Gold Ore" => Some(Ore::GoldOre),
             "Diamond" => Some(Ore::Diamond),
@@ -139,22 +484,35 @@ impl Ore {
     }
 }
 
-pub fn update_inventory_file(players: &Vec<Player>, file_path: &str) -> io::Result<()> {
+pub fn update_inventory_file(players: &[Player], file_path: &str) -> io::Result<()> {
     let mut file = File::create(file_path)?;
 
     for player in players {
         let mut inventory_string = String::new();
         inventory_string.push_str(&player.account.username);
-        inventory_string.push_str(";");
+        inventory_string.push(';');
 
-        for ore in player.inventory.ores {
-            if let Some(ore) = ore {
-                inventory_string.push_str(ore.to_string());
-                inventory_string.push_str(";");
-            }
+        for ore in player.inventory.ores.iter().flatten() {
+            inventory_string.push_str(ore.to_string());
+            inventory_string.push(';');
+        }
+
+        for food in player.inventory.foods.iter().flatten() {
+            inventory_string.push_str(food.to_string());
+            inventory_string.push(';');
+        }
+
+        for gear in player.inventory.gear.iter().flatten() {
+            inventory_string.push_str(gear.to_string());
+            inventory_string.push(';');
+        }
+
+        for consumable in player.inventory.consumables.iter().flatten() {
+            inventory_string.push_str(consumable.to_string());
+            inventory_string.push(';');
         }
 
-        inventory_string.push_str("\n");
+        inventory_string.push('\n');
         file.write_all(inventory_string.as_bytes())?;
     }
 
@@ -0,0 +1,79 @@
+// Player facing text lives in one catalog per language, `key = text`
+// per line. Placeholders are written {0}, {1}... so translations can
+// reorder them. Keys missing from a catalog fall back to English.
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+pub const LANGUAGES: [(&str, &str); 2] = [
+    ("en", include_str!("en.txt")),
+    ("id", include_str!("id.txt")),
+];
+
+struct Catalog {
+    selected: HashMap<&'static str, &'static str>,
+    fallback: HashMap<&'static str, &'static str>,
+}
+
+static CATALOG: OnceLock<Catalog> = OnceLock::new();
+
+fn parse(source: &'static str) -> HashMap<&'static str, &'static str> {
+    source
+        .lines()
+        .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        // trailing spaces are kept, prompts need them
+        .map(|(key, text)| (key.trim(), text.trim_start()))
+        .collect()
+}
+
+fn source(code: &str) -> Option<&'static str> {
+    LANGUAGES
+        .iter()
+        .find(|(language, _)| *language == code)
+        .map(|(_, source)| *source)
+}
+
+fn load(code: &str) -> Option<Catalog> {
+    Some(Catalog {
+        selected: parse(source(code)?),
+        fallback: parse(source("en")?),
+    })
+}
+
+// Has to be called before any text is looked up, English is used otherwise
+pub fn set_language(code: &str) -> Result<(), String> {
+    let catalog = load(code).ok_or(format!("Unknown language: {}", code))?;
+    CATALOG
+        .set(catalog)
+        .map_err(|_| "The language was already chosen".to_string())
+}
+
+// A missing key is shown as is, so it is easy to spot
+pub fn text(key: &str) -> &str {
+    let catalog = CATALOG.get_or_init(|| load("en").unwrap());
+    match catalog.selected.get(key).or(catalog.fallback.get(key)) {
+        Some(text) => text,
+        None => key,
+    }
+}
+
+pub fn fill(template: &str, args: &[&dyn Display]) -> String {
+    let mut result = template.to_string();
+    for (i, arg) in args.iter().enumerate() {
+        result = result.replace(&format!("{{{}}}", i), &arg.to_string());
+    }
+    result
+}
+
+// tr!("key") looks up a text, tr!("key", a, b) also fills in its placeholders
+#[macro_export]
+macro_rules! tr {
+    ($key:expr) => {
+        $crate::lang::text($key).to_string()
+    };
+    ($key:expr, $($arg:expr),+ $(,)?) => {
+        $crate::lang::fill($crate::lang::text($key), &[$(&$arg),+])
+    };
+}
+pub use crate::tr;
@@ -0,0 +1,32 @@
+// Everything shared by the game and the server binaries
+pub mod account;
+pub mod achievement;
+pub mod auction;
+pub mod bank;
+pub mod clock;
+pub mod consumable;
+pub mod effect;
+pub mod equipment;
+pub mod event;
+pub mod expedition;
+pub mod farm;
+pub mod game;
+pub mod input;
+pub mod inventory;
+pub mod lang;
+pub mod market;
+pub mod menu;
+pub mod mine;
+pub mod money;
+pub mod player;
+pub mod quest;
+pub mod save;
+pub mod script;
+pub mod server;
+pub mod stat;
+pub mod style;
+pub mod timestamp;
+pub mod trade;
+#[cfg(feature = "tui")]
+pub mod tui;
+pub mod world;
@@ -1,56 +1,96 @@
-use rand::Rng;
-use std::io::{self, Write};
+use std::cell::Cell;
+use std::io;
 
-mod account;
-use account::{parse_account_file, update_account_file, Account};
+use text_craft::auction::{self, AuctionHouse, Listing, MAX_DURATION};
+use text_craft::bank::{self, EntryKind, VAULT_SLOTS};
+use text_craft::clock::{self, Clock};
+use text_craft::consumable::{Consumable, ALL_CONSUMABLES};
+use text_craft::equipment::{Gear, ALL_GEAR, ALL_SLOTS};
+use text_craft::farm::{self, ALL_CROPS};
+use text_craft::input::{invalid_choice, read_line, wait_for_enter, Exit};
+use text_craft::inventory::{Item, Ore, ALL_ORES, SHOP_FOODS};
+use text_craft::lang::{text, tr};
+use text_craft::menu::{Menu, MenuItem, MenuResult};
+use text_craft::mine::{Direction, MAP_LEGEND, MAP_RADIUS};
+use text_craft::money::Money;
+use text_craft::player::*;
+use text_craft::quest::Quest;
+use text_craft::timestamp::format_time;
+use text_craft::trade::{self, Goods};
+#[cfg(feature = "tui")]
+use text_craft::tui;
+use text_craft::world::{World, WorldInfo, WORLDS_DIR};
+use text_craft::{game, input, lang, menu, script, style, world};
 
-mod inventory;
-use inventory::{search_inventory_file, Food, Inventory};
+fn main() -> Result<(), io::Error> {
+    let args: Vec<String> = std::env::args().collect();
 
-mod player;
-use player::*;
+    // --lang <code> picks the language of every text, English by default
+    if let Some(index) = args.iter().position(|arg| arg == "--lang") {
+        let code = args.get(index + 1).map(|x| x.as_str()).unwrap_or_default();
+        if let Err(msg) = lang::set_language(code) {
+            let codes: Vec<&str> = lang::LANGUAGES.iter().map(|(code, _)| *code).collect();
+            eprintln!("{} (available: {})", msg, codes.join(", "));
+            std::process::exit(2);
+        }
+    }
 
-use crate::inventory::update_inventory_file;
+    // --world <name> skips the world selection
+    let world_name = args
+        .iter()
+        .position(|arg| arg == "--world")
+        .and_then(|index| args.get(index + 1))
+        .map(|x| x.as_str());
 
-fn main() -> Result<(), io::Error> {
-    let account_file_path = "src/account.txt";
-    let inventory_file_path = "src/inventory.txt";
-    let accounts = parse_account_file(account_file_path)?;
-    let mut players: Vec<Player> = Vec::new();
-    for account in accounts {
-        players.push(Player::new(
-            search_inventory_file(inventory_file_path, &account.username)
-                .unwrap_or(Inventory::create_empty()),
-            account,
-        ));
+    // --autosave <n> saves after every n actions in the menus, 0 turns it off
+    if let Some(index) = args.iter().position(|arg| arg == "--autosave") {
+        match args.get(index + 1).and_then(|x| x.parse().ok()) {
+            Some(every) => menu::set_autosave(every),
+            None => {
+                eprintln!("--autosave needs a number of actions");
+                std::process::exit(2);
+            }
+        }
     }
 
-    loop {
-        println!("Welcome to Textcraft!");
-        println!("1. Continue");
-        println!("2. New Game");
-        println!("3. Exit");
-        print!(">> ");
-        unsafe_stdout_flush();
-
-        let mut choice = String::new();
-        io::stdin()
-            .read_line(&mut choice)
-            .expect("Failed to read input");
-
-        match choice.trim().parse::<i32>().unwrap_or(-1) {
-            1 => login_menu(&mut players),
-            2 => register_menu(&mut players),
-            3 => return Ok(()),
-            _ => (),
-        }
+    world::import_legacy_save(WORLDS_DIR)?;
 
-        let mut accounts: Vec<Account> = Vec::new();
-        for player in &players {
-            accounts.push(player.account.clone());
+    // --script <file> runs commands from a file, --script alone or with "-" reads stdin
+    if let Some(index) = args.iter().position(|arg| arg == "--script") {
+        let mut world = world::open_world_or_default(WORLDS_DIR, world_name)?;
+        let file_path = args.get(index + 1).filter(|arg| !arg.starts_with("--"));
+        return match file_path.map(|x| x.as_str()) {
+            None | Some("-") => script::run(&mut world, io::stdin().lock()),
+            Some(file_path) => {
+                let file = std::fs::File::open(file_path)?;
+                script::run(&mut world, io::BufReader::new(file))
+            }
+        };
+    }
+
+    #[cfg(feature = "tui")]
+    if args.iter().any(|arg| arg == "--tui") {
+        let mut world = world::open_world_or_default(WORLDS_DIR, world_name)?;
+        tui::run(&mut world)?;
+        return world::save_world(&mut world);
+    }
+
+    input::install_interrupt_handler();
+    let result = match world_name {
+        Some(name) => play_world(name),
+        None => title_menu(),
+    };
+    match result {
+        Ok(()) => Ok(()),
+        // the world that was open has already been saved
+        Err(Exit::Save) => {
+            println!("{}", text("main.saved_goodbye"));
+            Ok(())
+        }
+        Err(Exit::Discard) => {
+            println!("{}", text("main.discarded_goodbye"));
+            Ok(())
         }
-        update_account_file(&accounts, account_file_path)?;
-        update_inventory_file(&players, inventory_file_path)?;
     }
 }
 
@@ -63,315 +103,1640 @@ fn main() -> Result<(), io::Error> {
 //         .success());
 // }
 
-fn is_alphanumeric_string(input: &str) -> bool {
-    input.chars().all(|c| c.is_alphanumeric())
+fn print_log(log: &[String]) {
+    for message in log {
+        println!("{}", style::highlight(message));
+    }
 }
 
-fn unsafe_stdout_flush() {
-    io::stdout().flush().unwrap();
+// A failed action explains why in its last message
+fn print_outcome(done: bool, log: &[String]) {
+    match log.split_last() {
+        Some((reason, rest)) if !done => {
+            print_log(rest);
+            println!("{}", style::error(reason));
+        }
+        _ => print_log(log),
+    }
 }
 
-fn register_menu(players: &mut Vec<Player>) {
-    println!("Creating a new account:");
-    let mut input = String::new();
-    loop {
-        print!("Enter your username (Must be alphanumeric): ");
-        unsafe_stdout_flush();
+fn save_game(world: &mut World) -> MenuResult {
+    if let Err(e) = world::save_world(world) {
+        println!("{}", style::error(&tr!("main.save_failed", e)));
+    }
+    Ok(())
+}
 
-        input.clear();
-        io::stdin()
-            .read_line(&mut input)
-            .expect("Failed to read input");
+// Availability checks shared by the shop menus
+fn afford(player: &Player, price: Money) -> Result<(), String> {
+    if player.account.money < price {
+        return Err(tr!("menu.costs", price));
+    }
+    Ok(())
+}
 
-        input = input.trim().to_string();
-        if is_alphanumeric_string(&input) {
-            break;
-        }
+fn has_gear_space(player: &Player) -> Result<(), String> {
+    if !player.inventory.gear.iter().any(|x| x.is_none()) {
+        return Err(tr!("menu.gear_bag_full"));
     }
+    Ok(())
+}
 
-    let player = Player::new(Inventory::create_empty(), Account::new_account(input));
-    println!(
-        "Made an account with username: {}",
-        &player.account.username
-    );
+fn has_worlds() -> Result<(), String> {
+    match world::list_worlds(WORLDS_DIR) {
+        Ok(worlds) if !worlds.is_empty() => Ok(()),
+        _ => Err(tr!("title.no_world")),
+    }
+}
 
-    players.push(player);
-    wait_for_enter();
+fn describe_world(info: &WorldInfo) -> String {
+    tr!(
+        "world.entry",
+        info.name,
+        format_time(info.created),
+        format_time(info.last_played),
+        info.version
+    )
 }
 
-fn wait_for_enter() {
-    println!("Press Enter to continue...");
-    let mut input = String::new();
-    io::stdin()
-        .read_line(&mut input)
-        .expect("Failed to read line");
+fn title_menu() -> MenuResult {
+    Menu::new(|_: &()| println!("{}", style::bold(text("main.welcome"))))
+        .item(
+            MenuItem::new(text("title.play"), |_: &mut ()| {
+                choose_world_menu(text("world.choose_play"), play_world)
+            })
+            .hotkey('p')
+            .available_if(|_| has_worlds()),
+        )
+        .item(MenuItem::new(text("title.create"), |_: &mut ()| create_world_menu()).hotkey('n'))
+        .item(
+            MenuItem::new(text("title.copy"), |_: &mut ()| {
+                choose_world_menu(text("world.choose_copy"), copy_world_menu)
+            })
+            .hotkey('c')
+            .available_if(|_| has_worlds()),
+        )
+        .item(
+            MenuItem::new(text("title.delete"), |_: &mut ()| {
+                choose_world_menu(text("world.choose_delete"), delete_world_menu)
+            })
+            .hotkey('d')
+            .available_if(|_| has_worlds()),
+        )
+        .back(text("menu.exit"))
+        .run(&mut ())
 }
 
-fn login_menu(players: &mut Vec<Player>) {
-    if players.len() == 0 {
-        println!("No account found!");
-        wait_for_enter();
-        return;
+// Lists the worlds as they are right now and runs `action` on the chosen one
+fn choose_world_menu(title: &'static str, action: fn(&str) -> MenuResult) -> MenuResult {
+    let mut worlds = match world::list_worlds(WORLDS_DIR) {
+        Ok(worlds) => worlds,
+        Err(e) => {
+            println!("{}", style::error(&tr!("world.list_failed", e)));
+            return Ok(());
+        }
+    };
+
+    let mut menu = Menu::new(move |_: &Vec<WorldInfo>| println!("{}", title))
+        .back(text("menu.cancel"))
+        .once();
+    for i in 0..worlds.len() {
+        menu = menu.item(MenuItem::labeled(
+            move |worlds: &Vec<WorldInfo>| describe_world(&worlds[i]),
+            move |worlds: &mut Vec<WorldInfo>| action(&worlds[i].name),
+        ));
     }
+    menu.run(&mut worlds)
+}
 
+fn read_world_name() -> Result<String, Exit> {
     loop {
-        println!("Choose an account! (0 to return)");
-        let mut index = 1;
-        players.sort_by(|a, b| b.cmp(a));
-        for player in players.iter() {
-            println!(
-                "{}. {}, Money: {}",
-                index, player.account.username, player.account.money
-            );
-            index += 1;
+        print!("{}", text("world.name_prompt"));
+        input::unsafe_stdout_flush();
+
+        let input = read_line()?;
+        if world::is_valid_name(&input) {
+            return Ok(input);
         }
+    }
+}
 
-        print!(">> ");
-        unsafe_stdout_flush();
+fn create_world_menu() -> MenuResult {
+    println!("{}", text("world.create_title"));
+    let name = read_world_name()?;
+    match world::create_world(WORLDS_DIR, &name) {
+        Ok(info) => println!("{}", tr!("world.created", info.name)),
+        Err(e) => println!("{}", style::error(&tr!("world.create_failed", name, e))),
+    }
+    wait_for_enter()
+}
 
-        let mut input = String::new();
-        io::stdin()
-            .read_line(&mut input)
-            .expect("Failed to read line");
+fn copy_world_menu(from: &str) -> MenuResult {
+    println!("{}", tr!("world.copy_title", from));
+    let to = read_world_name()?;
+    match world::copy_world(WORLDS_DIR, from, &to) {
+        Ok(()) => println!("{}", tr!("world.copied", from, to)),
+        Err(e) => println!("{}", style::error(&tr!("world.create_failed", to, e))),
+    }
+    wait_for_enter()
+}
 
-        let input: i32 = input.trim().parse().unwrap_or(-1);
-        if input == 0 {
-            return;
-        }
+fn delete_world_menu(name: &str) -> MenuResult {
+    print!("{}", tr!("world.delete_confirm", name));
+    input::unsafe_stdout_flush();
+    if read_line()? != "y" {
+        return Ok(());
+    }
 
-        if input < 0 {
-            continue;
-        }
-        let index: usize = (input - 1).try_into().unwrap();
-        if index >= players.len() {
-            continue;
-        }
-        play_game(&mut players[index]);
+    match world::delete_world(WORLDS_DIR, name) {
+        Ok(()) => println!("{}", tr!("world.deleted", name)),
+        Err(e) => println!("{}", style::error(&tr!("world.delete_failed", name, e))),
     }
+    wait_for_enter()
 }
 
-fn play_game(player: &mut Player) {
-    loop {
-        println!("Welcome, {}!", player.account.username);
-        println!("1. Go mining\n2. Go shopping\n3. Back");
-
-        let mut choice = String::new();
-        io::stdin()
-            .read_line(&mut choice)
-            .expect("Failed to read input");
-
-        match choice.trim().parse::<i32>().unwrap_or(-1) {
-            1 => go_mining(player),
-            2 => go_shopping(player),
-            3 => return,
-            _ => (),
+// The world is saved when leaving it, unless the player quit without saving
+fn play_world(name: &str) -> MenuResult {
+    let mut world = match world::open_world(WORLDS_DIR, name) {
+        Ok(world) => world,
+        Err(e) => {
+            println!("{}", style::error(&tr!("world.open_failed", name, e)));
+            return wait_for_enter();
         }
+    };
+
+    let result = world_menu(&mut world);
+    if !matches!(result, Err(Exit::Discard)) {
+        save_game(&mut world)?;
     }
+    result
 }
 
-fn go_shopping(player: &mut Player) {
-    loop {
-        println!("Welcome to the shop!");
-        println!("Money: {}", player.account.money);
-        println!("1. Sell ores\n2. Buy items\n3. Back");
-
-        let mut choice = String::new();
-        io::stdin()
-            .read_line(&mut choice)
-            .expect("Failed to read input");
-
-        match choice.trim().parse::<i32>().unwrap_or(-1) {
-            1 => sell_ores_menu(player),
-            2 => buy_item_menu(player),
-            3 => return,
-            _ => (),
+fn world_menu(world: &mut World) -> MenuResult {
+    Menu::new(|world: &World| println!("{}", tr!("world.title", world.info.name)))
+        .item(
+            MenuItem::new(text("title.continue"), |world: &mut World| {
+                login_menu(world)?;
+                save_game(world)
+            })
+            .hotkey('c')
+            .available_if(|world| match world.players.is_empty() {
+                true => Err(tr!("title.no_account")),
+                false => Ok(()),
+            }),
+        )
+        .item(
+            MenuItem::new(text("title.new_game"), |world: &mut World| {
+                register_menu(world)?;
+                save_game(world)
+            })
+            .hotkey('n'),
+        )
+        .back(text("menu.return"))
+        .saves(world::save_world)
+        .run(world)
+}
+
+fn register_menu(world: &mut World) -> MenuResult {
+    println!("{}", text("register.title"));
+    let player = loop {
+        print!("{}", text("register.prompt"));
+        input::unsafe_stdout_flush();
+
+        match world.add_player(read_line()?) {
+            Ok(player) => break player,
+            Err(e) => println!("{}", style::error(e.message())),
         }
+    };
+    println!("{}", tr!("register.done", player.account.username));
+    wait_for_enter()
+}
+
+fn login_menu(world: &mut World) -> MenuResult {
+    world.players.sort_by(|a, b| b.cmp(a));
+
+    let mut menu = Menu::new(|_: &World| println!("{}", text("login.title")))
+        .back(text("menu.return"))
+        .saves(world::save_world);
+    for i in 0..world.players.len() {
+        menu = menu.item(MenuItem::labeled(
+            move |world: &World| {
+                let account = &world.players[i].account;
+                tr!("login.account", account.username, account.money)
+            },
+            move |world: &mut World| {
+                play_game(&mut Session { world, index: i })?;
+                world.players.sort_by(|a, b| b.cmp(a));
+                Ok(())
+            },
+        ));
     }
+    menu.run(world)
 }
 
-fn buy_item_menu(player: &mut Player) {
-    loop {
-        println!("Your food bag:");
-        player.inventory.print_food();
-        println!("Your money: {}", player.account.money);
+// One player's turn, every menu in it can save the whole world
+struct Session<'a> {
+    world: &'a mut World,
+    index: usize,
+}
+
+impl Session<'_> {
+    fn player(&self) -> &Player {
+        &self.world.players[self.index]
+    }
+
+    fn player_mut(&mut self) -> &mut Player {
+        &mut self.world.players[self.index]
+    }
+
+    fn username(&self) -> &str {
+        &self.player().account.username
+    }
+
+    // The player along with the world's clock, for actions that take time
+    fn player_and_clock(&mut self) -> (&mut Player, &mut Clock) {
+        let world = &mut *self.world;
+        (&mut world.players[self.index], &mut world.info.clock)
+    }
+
+    fn save(&mut self) -> io::Result<()> {
+        world::save_world(self.world)
+    }
+}
+
+fn play_game(session: &mut Session) -> MenuResult {
+    // a trip that was cut short carries on before anything else
+    if session.player().is_in_mine() {
+        go_mining(session)?;
+    }
+
+    Menu::new(|session: &Session| {
+        println!(
+            "{}",
+            tr!("main.welcome_player", session.player().account.username)
+        );
+        println!("{}", session.world.info.clock.describe());
+    })
+    .item(MenuItem::new(text("play.mining"), go_mining).hotkey('m'))
+    .item(
+        MenuItem::new(text("play.shopping"), go_shopping)
+            .hotkey('s')
+            .available_if(|session| match session.world.info.clock.is_shop_open() {
+                true => Ok(()),
+                false => Err(clock::shop_closed_message()),
+            }),
+    )
+    .item(
+        MenuItem::new(text("play.wait"), |session: &mut Session| {
+            let mut log = Vec::new();
+            let (player, clock) = session.player_and_clock();
+            let done = game::wait_for_shop(player, clock, &mut log);
+            print_outcome(done, &log);
+            Ok(())
+        })
+        .hotkey('z')
+        .available_if(|session| match session.world.info.clock.is_shop_open() {
+            true => Err(tr!("menu.shop_open")),
+            false => Ok(()),
+        }),
+    )
+    .item(MenuItem::new(text("play.farm"), farm_menu).hotkey('f'))
+    .item(
+        MenuItem::new(text("play.cook"), cook_food)
+            .hotkey('c')
+            .available_if(|session| {
+                let foods = &session.player().inventory.foods;
+                match foods.iter().flatten().any(|food| food.cooked().is_some()) {
+                    true => Ok(()),
+                    false => Err(tr!("menu.nothing_to_cook")),
+                }
+            }),
+    )
+    .item(MenuItem::new(text("play.equipment"), equipment_menu).hotkey('e'))
+    .item(MenuItem::new(text("play.trades"), trades_menu).hotkey('t'))
+    .item(MenuItem::new(text("play.bank"), bank_menu).hotkey('k'))
+    .item(MenuItem::new(text("play.auction"), auction_menu).hotkey('h'))
+    .item(
+        MenuItem::new(text("play.achievements"), |session: &mut Session| {
+            achievements_menu(session.player())
+        })
+        .hotkey('a'),
+    )
+    .saves(Session::save)
+    .run(session)
+}
+
+fn go_shopping(session: &mut Session) -> MenuResult {
+    Menu::new(|session: &Session| {
+        println!("{}", text("shop.welcome"));
+        println!("{}", tr!("shop.money", session.player().account.money));
+    })
+    .item(MenuItem::new(text("shop.sell_ores"), sell_ores_menu).hotkey('s'))
+    .item(MenuItem::new(text("shop.buy_items"), buy_item_menu).hotkey('i'))
+    .item(MenuItem::new(text("shop.armory"), armory_menu).hotkey('a'))
+    .item(MenuItem::new(text("shop.supplies"), supplies_menu).hotkey('u'))
+    .item(MenuItem::new(text("shop.quest_board"), quest_board_menu).hotkey('q'))
+    .saves(Session::save)
+    .run(session)
+}
+
+fn buy_item_menu(session: &mut Session) -> MenuResult {
+    let mut menu = Menu::new(|session: &Session| {
+        println!("{}", text("shop.food_bag"));
+        session.player().inventory.print_food();
+        println!("{}", tr!("shop.your_money", session.player().account.money));
         println!("=====================");
-        println!("1. Buy Apple - $30\n2. Buy Chicken - $70\n3. Buy Beef - $90");
-        if !player.is_pickaxe_maxed() {
-            println!("4. Upgrade Pickaxe - ${}", player.upgrade_pickaxe_cost());
-            print!("5. Return\n>> ");
-        } else {
-            print!("4. Return\n>> ");
-        }
-        unsafe_stdout_flush();
-
-        let mut choice = String::new();
-        io::stdin()
-            .read_line(&mut choice)
-            .expect("Failed to read input");
-
-        let choice = choice.trim().parse::<i32>().unwrap_or(-1);
-        if choice == 4 && !player.is_pickaxe_maxed() {
-            match player.spend(player.upgrade_pickaxe_cost()) {
-                Ok(_) => {
-                    player.upgrade_pickaxe();
-                    println!("Upgraded pickaxe level!");
-                    wait_for_enter();
-                    continue;
+    })
+    .back(text("menu.return"))
+    .saves(Session::save);
+
+    for food in SHOP_FOODS {
+        let item = MenuItem::new(
+            tr!("shop.buy_food", food.name(), food.price()),
+            move |session: &mut Session| {
+                let mut log = Vec::new();
+                let done = game::buy_food(session.player_mut(), food, &mut log);
+                print_outcome(done, &log);
+                Ok(())
+            },
+        )
+        .available_if(move |session| {
+            let player = session.player();
+            if !player.inventory.foods.iter().any(|x| x.is_none()) {
+                return Err(tr!("menu.food_bag_full"));
+            }
+            afford(player, food.price())
+        });
+        menu = menu.item(item);
+    }
+
+    // stays listed once maxed so the numbering never shifts
+    menu.item(
+        MenuItem::labeled(
+            |session: &Session| {
+                let player = session.player();
+                match player.is_pickaxe_maxed() {
+                    true => tr!("shop.upgrade_pickaxe"),
+                    false => tr!("shop.upgrade_pickaxe_cost", player.upgrade_pickaxe_cost()),
                 }
-                Err(msg) => {
-                    println!("{}", msg);
-                    wait_for_enter();
-                    continue;
+            },
+            |session: &mut Session| {
+                let mut log = Vec::new();
+                let done = game::upgrade_pickaxe(session.player_mut(), &mut log);
+                print_outcome(done, &log);
+                Ok(())
+            },
+        )
+        .hotkey('p')
+        .available_if(|session| {
+            let player = session.player();
+            if player.is_pickaxe_maxed() {
+                return Err(tr!("menu.max_level"));
+            }
+            afford(player, player.upgrade_pickaxe_cost())
+        }),
+    )
+    .run(session)
+}
+
+fn supplies_menu(session: &mut Session) -> MenuResult {
+    let mut menu = Menu::new(|session: &Session| {
+        println!("{}", text("shop.supply_bag"));
+        session.player().inventory.print_consumables();
+        println!("{}", tr!("shop.your_money", session.player().account.money));
+        println!("=====================");
+    })
+    .back(text("menu.return"))
+    .saves(Session::save);
+
+    for consumable in ALL_CONSUMABLES {
+        let label = tr!(
+            "shop.supply",
+            consumable.name(),
+            consumable.price(),
+            consumable.description()
+        );
+        let item = MenuItem::new(label, move |session: &mut Session| {
+            let mut log = Vec::new();
+            let done = game::buy_consumable(session.player_mut(), consumable, &mut log);
+            print_outcome(done, &log);
+            Ok(())
+        })
+        .available_if(move |session| {
+            let player = session.player();
+            if !player.inventory.consumables.iter().any(|x| x.is_none()) {
+                return Err(tr!("menu.supply_bag_full"));
+            }
+            afford(player, consumable.price())
+        });
+        menu = menu.item(item);
+    }
+    menu.run(session)
+}
+
+fn armory_menu(session: &mut Session) -> MenuResult {
+    let mut menu = Menu::new(|session: &Session| {
+        println!("{}", text("shop.gear_bag"));
+        session.player().inventory.print_gear();
+        println!("{}", tr!("shop.your_money", session.player().account.money));
+        println!("=====================");
+    })
+    .saves(Session::save);
+
+    for gear in ALL_GEAR {
+        let (ore, amount) = gear.recipe();
+        let label = tr!(
+            "armory.gear",
+            gear.name(),
+            gear.slot().name(),
+            gear.price(),
+            amount,
+            ore.name()
+        );
+        menu = menu.item(MenuItem::new(label, move |session: &mut Session| {
+            gear_menu(session, gear)
+        }));
+    }
+    menu.run(session)
+}
+
+fn gear_menu(session: &mut Session, gear: Gear) -> MenuResult {
+    let (ore, amount) = gear.recipe();
+    Menu::new(move |_: &Session| println!("{}:", gear.name()))
+        .item(
+            MenuItem::new(text("armory.buy"), move |session: &mut Session| {
+                let mut log = Vec::new();
+                let done = game::buy_gear(session.player_mut(), gear, &mut log);
+                print_outcome(done, &log);
+                Ok(())
+            })
+            .available_if(move |session| {
+                has_gear_space(session.player())?;
+                afford(session.player(), gear.price())
+            }),
+        )
+        .item(
+            MenuItem::new(text("armory.craft"), move |session: &mut Session| {
+                let mut log = Vec::new();
+                let (player, clock) = session.player_and_clock();
+                let done = game::craft_gear(player, clock, gear, &mut log);
+                print_outcome(done, &log);
+                Ok(())
+            })
+            .available_if(move |session| {
+                has_gear_space(session.player())?;
+                if session.player().inventory.count_ore(ore) < amount {
+                    return Err(tr!("menu.needs_ore", amount, ore.name()));
                 }
-            };
+                Ok(())
+            }),
+        )
+        .back(text("menu.cancel"))
+        .once()
+        .saves(Session::save)
+        .run(session)
+}
+
+fn equipment_menu(session: &mut Session) -> MenuResult {
+    Menu::new(|session: &Session| {
+        println!("{}", text("equipment.equipped"));
+        session.player().equipment.print();
+        println!("{}", text("shop.gear_bag"));
+        session.player().inventory.print_gear();
+    })
+    .item(
+        MenuItem::new(text("equipment.equip"), equip_menu).available_if(|session| {
+            if session.player().inventory.gear.iter().all(|x| x.is_none()) {
+                return Err(tr!("menu.gear_bag_empty"));
+            }
+            Ok(())
+        }),
+    )
+    .item(
+        MenuItem::new(text("equipment.unequip"), unequip_menu).available_if(|session| {
+            let equipment = &session.player().equipment;
+            if ALL_SLOTS.iter().all(|slot| equipment.get(*slot).is_none()) {
+                return Err(tr!("menu.nothing_equipped"));
+            }
+            Ok(())
+        }),
+    )
+    .saves(Session::save)
+    .run(session)
+}
+
+fn equip_menu(session: &mut Session) -> MenuResult {
+    let mut menu = Menu::new(|_: &Session| println!("{}", text("equipment.choose_gear")))
+        .back(text("menu.cancel"))
+        .once()
+        .saves(Session::save);
+
+    for i in 0..session.player().inventory.gear.len() {
+        let item = MenuItem::labeled(
+            move |session: &Session| {
+                match session.player().inventory.gear[i] {
+                    Some(gear) => gear.name(),
+                    None => text("item.empty"),
+                }
+                .to_string()
+            },
+            move |session: &mut Session| {
+                let player = session.player_mut();
+                if let Some(gear) = player.inventory.gear[i].take() {
+                    if let Some(previous) = player.equipment.equip(gear) {
+                        player.inventory.push_gear(previous);
+                    }
+                    println!("{}", tr!("equipment.equipped_gear", gear.name()));
+                }
+                Ok(())
+            },
+        )
+        .available_if(move |session| match session.player().inventory.gear[i] {
+            Some(_) => Ok(()),
+            None => Err(tr!("menu.empty_slot")),
+        });
+        menu = menu.item(item);
+    }
+    menu.run(session)
+}
+
+fn unequip_menu(session: &mut Session) -> MenuResult {
+    let mut menu = Menu::new(|_: &Session| println!("{}", text("equipment.choose_slot")))
+        .back(text("menu.cancel"))
+        .once()
+        .saves(Session::save);
+
+    for slot in ALL_SLOTS {
+        let item = MenuItem::labeled(
+            move |session: &Session| {
+                let gear = match session.player().equipment.get(slot) {
+                    Some(gear) => gear.name(),
+                    None => text("item.empty"),
+                };
+                format!("{}: {}", slot.name(), gear)
+            },
+            move |session: &mut Session| {
+                let player = session.player_mut();
+                if let Some(gear) = player.equipment.unequip(slot) {
+                    player.inventory.push_gear(gear);
+                    println!("{}", tr!("equipment.unequipped_gear", gear.name()));
+                }
+                Ok(())
+            },
+        )
+        .available_if(move |session| {
+            if session.player().equipment.get(slot).is_none() {
+                return Err(tr!("menu.nothing_equipped_there"));
+            }
+            has_gear_space(session.player())
+        });
+        menu = menu.item(item);
+    }
+    menu.run(session)
+}
+
+fn sell_ores_menu(session: &mut Session) -> MenuResult {
+    Menu::new(|session: &Session| {
+        let player = session.player();
+        println!("{}", tr!("shop.your_money", player.account.money));
+        println!("{}", text("shop.your_ores"));
+        for ore in ALL_ORES {
+            println!(
+                "{}",
+                tr!(
+                    "shop.ore_count",
+                    style::item(ore.name(), ore.rarity()),
+                    player.inventory.count_ore(ore),
+                    session.world.market.price(ore),
+                    session.world.market.percent(ore)
+                )
+            );
         }
+    })
+    .item(
+        MenuItem::new(text("shop.sell_all"), |session: &mut Session| {
+            let mut log = Vec::new();
+            let world = &mut *session.world;
+            game::sell_all_ores(
+                &mut world.players[session.index],
+                &mut world.market,
+                &mut log,
+            );
+            print_log(&log);
+            Ok(())
+        })
+        .available_if(|session| {
+            if session.player().inventory.ores.iter().all(|x| x.is_none()) {
+                return Err(tr!("menu.no_ores"));
+            }
+            Ok(())
+        }),
+    )
+    .saves(Session::save)
+    .run(session)
+}
 
-        let (price, food) = match choice {
-            1 => (30, Food::Apple),
-            2 => (70, Food::Chicken),
-            3 => (90, Food::Beef),
-            4 | 5 => return,
-            _ => continue,
-        };
+fn go_mining(session: &mut Session) -> MenuResult {
+    let mut log = Vec::new();
+    let (player, clock) = session.player_and_clock();
+    game::enter_mine(player, clock, &mut log);
+    print_log(&log);
 
-        if !player.inventory.foods.iter().any(|&x| x.is_none()) {
-            println!("You have no free space!");
-            continue;
+    // set when the player leaves the mine some other way than walking out
+    let surfaced = Cell::new(false);
+    let mut menu = Menu::new(|session: &Session| {
+        let player = session.player();
+        println!("{}", session.world.info.clock.describe());
+        println!("{}", text("mining.inventory"));
+        player.inventory.print_ores();
+        print_map(player);
+        let (x, y, depth) = player.mine.position();
+        println!("{}", tr!("mining.position", x, y, depth));
+        if depth > 1 {
+            println!("{}", tr!("mining.way_up", depth - 1));
         }
+        println!(
+            "{} {}",
+            style::meter(player.get_health(), MAX_HEALTH),
+            tr!("mining.health", player.get_health())
+        );
+        println!(
+            "{} {}",
+            style::meter(player.get_hunger(), MAX_HUNGER),
+            tr!("mining.hunger", player.get_hunger(), MAX_HUNGER)
+        );
+        println!("{}", tr!("mining.effects", player.effects.describe()));
+        println!("{}", text("mining.what_to_do"));
+    })
+    .back(text("menu.return"))
+    .saves(Session::save);
+
+    for (label, hotkey, direction) in [
+        ("mining.north", 'n', Direction::North),
+        ("mining.south", 's', Direction::South),
+        ("mining.east", 'e', Direction::East),
+        ("mining.west", 'w', Direction::West),
+        ("mining.down", 'd', Direction::Down),
+    ] {
+        let item = MenuItem::new(text(label), move |session: &mut Session| {
+            let mut log = Vec::new();
+            let (player, clock) = session.player_and_clock();
+            if game::dig(player, clock, direction, &mut log) {
+                let username = session.username().to_string();
+                let world = &mut *session.world;
+                auction::advance(&mut world.players, &mut world.auctions, &username, &mut log);
+            }
+            print_log(&log);
+            Ok(())
+        })
+        .hotkey(hotkey)
+        .available_if(move |session| {
+            let player = session.player();
+            if !player.is_alive() {
+                return Err(tr!("menu.no_health"));
+            }
+            if !player.mine.can_move(direction) {
+                return Err(tr!("menu.bedrock"));
+            }
+            Ok(())
+        });
+        menu = menu.item(item);
+    }
+
+    menu.item(
+        MenuItem::new(text("mining.eat"), eat_food)
+            .hotkey('f')
+            .available_if(|session| {
+                if session.player().inventory.foods.iter().all(|x| x.is_none()) {
+                    return Err(tr!("menu.food_bag_empty"));
+                }
+                Ok(())
+            }),
+    )
+    .item(
+        MenuItem::new(text("mining.use_item"), |session: &mut Session| {
+            use_item_menu(session, &surfaced)
+        })
+        .hotkey('u')
+        .available_if(|session| {
+            if session
+                .player()
+                .inventory
+                .consumables
+                .iter()
+                .all(|x| x.is_none())
+            {
+                return Err(tr!("menu.supply_bag_empty"));
+            }
+            Ok(())
+        }),
+    )
+    .close_when(|_| surfaced.get())
+    .run(session)?;
 
-        if let Err(msg) = player.spend(price) {
-            println!("{}", msg);
-            wait_for_enter();
-            continue;
+    if surfaced.get() {
+        return Ok(());
+    }
+    let mut log = Vec::new();
+    let (player, clock) = session.player_and_clock();
+    game::leave_mine(player, clock, &mut log);
+    print_log(&log);
+    Ok(())
+}
+
+fn use_item_menu(session: &mut Session, surfaced: &Cell<bool>) -> MenuResult {
+    let mut menu = Menu::new(|session: &Session| {
+        println!("{}", text("mining.supply_bag"));
+        session.player().inventory.print_consumables();
+    })
+    .back(text("menu.cancel"))
+    .once()
+    .saves(Session::save);
+
+    for consumable in ALL_CONSUMABLES {
+        let owned = move |session: &Session| {
+            let item = Item::Consumable(consumable);
+            session.player().inventory.count_item(item)
+        };
+        let label = move |session: &Session| tr!("trade.owned", consumable.name(), owned(session));
+        let use_it = move |session: &mut Session| {
+            let mut log = Vec::new();
+            let (player, clock) = session.player_and_clock();
+            let done = game::use_consumable(player, clock, consumable, &mut log);
+            print_outcome(done, &log);
+            surfaced.set(done && consumable == Consumable::Rope);
+            Ok(())
+        };
+        menu = menu.item(
+            MenuItem::labeled(label, use_it).available_if(move |session| {
+                if owned(session) == 0 {
+                    return Err(tr!("menu.none_owned"));
+                }
+                if consumable.mine_only() && !session.player().is_in_mine() {
+                    return Err(tr!("menu.only_in_mine"));
+                }
+                if consumable == Consumable::Tnt && !session.player().mine.can_move(Direction::Down)
+                {
+                    return Err(tr!("menu.bedrock"));
+                }
+                Ok(())
+            }),
+        );
+    }
+    menu.run(session)
+}
+
+fn print_map(player: &Player) {
+    let rows = player.mine.render(MAP_RADIUS);
+    let width = rows[0].len();
+    let border = format!("+{}+", "-".repeat(width));
+    println!("{}", border);
+    // show the legend next to the map, spilling past it if the map is shorter
+    for i in 0..rows.len().max(MAP_LEGEND.len()) {
+        let row = match rows.get(i) {
+            Some(row) => format!("|{}|", style::map_row(row)),
+            None if i == rows.len() => border.clone(),
+            None => " ".repeat(width + 2),
+        };
+        match MAP_LEGEND.get(i) {
+            Some((glyph, name)) => println!(
+                "{}  '{}' {}",
+                row,
+                style::map_row(&glyph.to_string()),
+                text(name)
+            ),
+            None => println!("{}", row),
         }
+    }
+    if rows.len() >= MAP_LEGEND.len() {
+        println!("{}", border);
+    }
+}
+
+fn eat_food(session: &mut Session) -> MenuResult {
+    let mut menu = Menu::new(|_: &Session| println!("{}", text("mining.food_sack")))
+        .back(text("menu.return"))
+        .saves(Session::save);
 
-        println!("Buying {} at {}$", food.to_string(), price);
-        player.inventory.push_food(food);
+    for i in 0..session.player().inventory.foods.len() {
+        let index = i as i32 + 1;
+        let item = MenuItem::labeled(
+            move |session: &Session| {
+                match session.player().inventory.foods[i] {
+                    Some(food) => food.name(),
+                    None => text("item.empty"),
+                }
+                .to_string()
+            },
+            move |session: &mut Session| {
+                let mut log = Vec::new();
+                let done = game::eat(session.player_mut(), index, &mut log);
+                print_outcome(done, &log);
+                Ok(())
+            },
+        )
+        .available_if(move |session| match session.player().can_eat(index) {
+            true => Ok(()),
+            false => Err(tr!("menu.empty_slot")),
+        });
+        menu = menu.item(item);
     }
+    menu.run(session)
 }
 
-fn sell_ores_menu(player: &mut Player) {
-    loop {
-        // count amount of each ore
-        let mut iron_count = 0;
-        let mut gold_count = 0;
-        let mut diamond_count = 0;
-        for ore in player.inventory.ores {
-            if let Some(ore) = ore {
-                match ore {
-                    inventory::Ore::IronOre => iron_count += 1,
-                    inventory::Ore::Diamond => diamond_count += 1,
-                    inventory::Ore::GoldOre => gold_count += 1,
+fn cook_food(session: &mut Session) -> MenuResult {
+    let mut menu = Menu::new(|session: &Session| {
+        println!("{}", text("cook.food_bag"));
+        let coal = session.player().inventory.count_ore(Ore::Coal);
+        println!("{}", tr!("cook.fuel", Ore::Coal.name(), coal));
+    })
+    .back(text("menu.return"))
+    .saves(Session::save);
+
+    for i in 0..session.player().inventory.foods.len() {
+        let index = i as i32 + 1;
+        let item = MenuItem::labeled(
+            move |session: &Session| {
+                match session.player().inventory.foods[i] {
+                    Some(food) => food.name(),
+                    None => text("item.empty"),
                 }
+                .to_string()
+            },
+            move |session: &mut Session| {
+                let mut log = Vec::new();
+                let (player, clock) = session.player_and_clock();
+                let done = game::cook(player, clock, index, &mut log);
+                print_outcome(done, &log);
+                Ok(())
+            },
+        )
+        .available_if(move |session| {
+            let player = session.player();
+            match player.inventory.foods[i] {
+                None => Err(tr!("menu.empty_slot")),
+                Some(food) if food.cooked().is_none() => Err(tr!("menu.not_raw")),
+                Some(_) if player.inventory.count_ore(Ore::Coal) == 0 => Err(tr!("menu.no_fuel")),
+                Some(_) => Ok(()),
             }
+        });
+        menu = menu.item(item);
+    }
+    menu.run(session)
+}
+
+// The quests on offer only last while the board is open
+struct QuestBoard<'a, 'b> {
+    session: &'a mut Session<'b>,
+    offers: Vec<Quest>,
+}
+
+impl QuestBoard<'_, '_> {
+    fn save(&mut self) -> io::Result<()> {
+        self.session.save()
+    }
+}
+
+fn quest_board_menu(session: &mut Session) -> MenuResult {
+    let mut board = QuestBoard {
+        session,
+        offers: (0..3).map(|_| Quest::generate()).collect(),
+    };
+
+    Menu::new(|board: &QuestBoard| {
+        let quests = &board.session.player().quests;
+        println!("{}", text("quest.active"));
+        if quests.active.is_empty() {
+            println!("- {}", text("common.none"));
         }
-        println!("Your money: {}", player.account.money);
-        println!("Your ores:");
-        println!("- Iron ores: {} @ 20$ per piece", iron_count);
-        println!("- Gold ores: {} @ 50$ per piece", gold_count);
-        println!("- Diamonds: {} @ 120$ per piece", diamond_count);
-        println!("1. Sell all\n2. Back");
-        print!(">> ");
-        unsafe_stdout_flush();
-
-        let mut choice = String::new();
-        io::stdin()
-            .read_line(&mut choice)
-            .expect("Failed to read input");
-
-        let choice = choice.trim().parse::<i32>().unwrap_or(-1);
-        if choice == 2 {
-            return;
+        for (i, quest) in quests.active.iter().enumerate() {
+            println!("{}. {}", i + 1, quest.describe());
         }
-        if choice != 1 {
-            return;
+        println!("=====================");
+        println!("{}", text("quest.board"));
+        for quest in &board.offers {
+            println!("- {}", quest.describe());
         }
+    })
+    .item(
+        MenuItem::new(text("quest.accept"), accept_quest_menu)
+            .hotkey('a')
+            .available_if(|board| {
+                if board.session.player().quests.is_full() {
+                    return Err(tr!("menu.quest_log_full"));
+                }
+                if board.offers.is_empty() {
+                    return Err(tr!("menu.board_empty"));
+                }
+                Ok(())
+            }),
+    )
+    .item(
+        MenuItem::new(text("quest.turn_in"), |board: &mut QuestBoard| {
+            let mut log = Vec::new();
+            game::turn_in_quests(board.session.player_mut(), &mut log);
+            print_log(&log);
+            wait_for_enter()
+        })
+        .hotkey('t'),
+    )
+    .saves(QuestBoard::save)
+    .run(&mut board)
+}
 
-        let total_added_money = iron_count * 20 + gold_count * 50 + diamond_count * 120;
-        player.account.money += total_added_money;
-        player.purge_inventory();
+fn accept_quest_menu(board: &mut QuestBoard) -> MenuResult {
+    let mut menu = Menu::new(|_: &QuestBoard| println!("{}", text("quest.choose")))
+        .back(text("menu.cancel"))
+        .once()
+        .saves(QuestBoard::save);
+
+    for i in 0..board.offers.len() {
+        menu = menu.item(MenuItem::labeled(
+            move |board: &QuestBoard| board.offers[i].describe(),
+            move |board: &mut QuestBoard| {
+                let quest = board.offers.remove(i);
+                println!("{}", tr!("quest.accepted", quest.describe()));
+                board.session.player_mut().quests.active.push(quest);
+                Ok(())
+            },
+        ));
     }
+    menu.run(board)
 }
 
-fn go_mining(player: &mut Player) {
-    player.stop_mining();
+fn read_amount<T: std::str::FromStr>() -> Result<T, Exit> {
+    read_number(text("trade.amount_prompt"))
+}
+
+fn read_number<T: std::str::FromStr>(prompt: &str) -> Result<T, Exit> {
     loop {
-        println!("Your inventory:");
-        player.inventory.print_ores();
-        println!("You're on depth: {}", player.get_depth());
-        println!("Health: {}", player.get_health());
-        println!("What to do?");
-        print!("1. Go Deeper\n2. Eat Food\n3. Return\n>> ");
-        unsafe_stdout_flush();
-
-        let mut choice = String::new();
-        io::stdin()
-            .read_line(&mut choice)
-            .expect("Failed to read input");
-
-        match choice.trim().parse::<i32>().unwrap_or(-1) {
-            1 => {
-                if player.is_alive() {
-                    dig(player);
-                } else {
-                    println!("You don't have enough health!");
-                }
-            }
-            2 => eat_food(player),
-            3 => return,
-            _ => (),
+        print!("{}", prompt);
+        input::unsafe_stdout_flush();
+
+        match read_line()?.parse() {
+            Ok(number) => return Ok(number),
+            Err(_) => invalid_choice(),
         }
     }
 }
 
-fn try_starting_event(chance: u8) -> bool {
-    let mut rng = rand::thread_rng();
-    let random_num: u8 = rng.gen_range(1..=100);
-    random_num <= chance
+fn trades_menu(session: &mut Session) -> MenuResult {
+    Menu::new(|session: &Session| {
+        let trades = &session.world.trades;
+        println!("{}", text("trade.incoming"));
+        let mut incoming = trade::incoming(trades, session.username()).peekable();
+        if incoming.peek().is_none() {
+            println!("- {}", text("common.none"));
+        }
+        for trade in incoming {
+            let (give, want) = (trade.give.describe(), trade.want.describe());
+            println!(
+                "- {}",
+                tr!("trade.incoming_entry", trade.id, trade.from, give, want)
+            );
+        }
+        println!("{}", text("trade.outgoing"));
+        let mut outgoing = trade::outgoing(trades, session.username()).peekable();
+        if outgoing.peek().is_none() {
+            println!("- {}", text("common.none"));
+        }
+        for trade in outgoing {
+            let (give, want) = (trade.give.describe(), trade.want.describe());
+            println!(
+                "- {}",
+                tr!("trade.outgoing_entry", trade.id, trade.to, give, want)
+            );
+        }
+    })
+    .item(
+        MenuItem::new(text("trade.make_offer"), choose_partner_menu)
+            .hotkey('o')
+            .available_if(|session| match session.world.players.len() > 1 {
+                true => Ok(()),
+                false => Err(tr!("menu.no_partner")),
+            }),
+    )
+    .item(
+        MenuItem::new(text("trade.answer"), |session: &mut Session| {
+            let trades = &session.world.trades;
+            let ids = trade::incoming(trades, session.username())
+                .map(|x| x.id)
+                .collect();
+            choose_offer_menu(session, ids, answer_offer_menu)
+        })
+        .hotkey('a')
+        .available_if(|session| {
+            match trade::incoming(&session.world.trades, session.username()).next() {
+                Some(_) => Ok(()),
+                None => Err(tr!("menu.no_offers")),
+            }
+        }),
+    )
+    .item(
+        MenuItem::new(text("trade.withdraw"), |session: &mut Session| {
+            let trades = &session.world.trades;
+            let ids = trade::outgoing(trades, session.username())
+                .map(|x| x.id)
+                .collect();
+            choose_offer_menu(session, ids, cancel_offer)
+        })
+        .hotkey('w')
+        .available_if(|session| {
+            match trade::outgoing(&session.world.trades, session.username()).next() {
+                Some(_) => Ok(()),
+                None => Err(tr!("menu.no_offers")),
+            }
+        }),
+    )
+    .saves(Session::save)
+    .run(session)
 }
 
-fn dig(player: &mut Player) {
-    // cause a random event
-    // Successfully dig
-    if try_starting_event(40) {
-        println!("You successfully dug deeper!");
-        player.go_deeper();
+fn describe_offer(session: &Session, id: u32) -> String {
+    let Some(trade) = session.world.trades.iter().find(|x| x.id == id) else {
+        return text("item.empty").to_string();
+    };
+    let (give, want) = (trade.give.describe(), trade.want.describe());
+    match trade.to == session.username() {
+        true => tr!("trade.incoming_entry", trade.id, trade.from, give, want),
+        false => tr!("trade.outgoing_entry", trade.id, trade.to, give, want),
     }
-    // get hungry
-    else if try_starting_event(20) {
-        player.take_damage(10);
+}
+
+fn choose_offer_menu(
+    session: &mut Session,
+    ids: Vec<u32>,
+    action: fn(&mut Session, u32) -> MenuResult,
+) -> MenuResult {
+    let mut menu = Menu::new(|_: &Session| println!("{}", text("trade.choose_offer")))
+        .back(text("menu.cancel"))
+        .once()
+        .saves(Session::save);
+    for id in ids {
+        menu = menu.item(MenuItem::labeled(
+            move |session: &Session| describe_offer(session, id),
+            move |session: &mut Session| action(session, id),
+        ));
     }
-    // creeper explode
-    else if try_starting_event(10) {
-        player.take_damage(30);
+    menu.run(session)
+}
+
+fn answer_offer_menu(session: &mut Session, id: u32) -> MenuResult {
+    Menu::new(move |session: &Session| println!("{}", describe_offer(session, id)))
+        .item(MenuItem::new(
+            text("trade.accept"),
+            move |session: &mut Session| {
+                let mut log = Vec::new();
+                let username = session.username().to_string();
+                let world = &mut *session.world;
+                let done = trade::accept(
+                    &mut world.players,
+                    &mut world.trades,
+                    &username,
+                    id,
+                    &mut log,
+                );
+                print_outcome(done, &log);
+                Ok(())
+            },
+        ))
+        .item(MenuItem::new(
+            text("trade.decline"),
+            move |session: &mut Session| cancel_offer(session, id),
+        ))
+        .back(text("menu.cancel"))
+        .once()
+        .saves(Session::save)
+        .run(session)
+}
+
+fn cancel_offer(session: &mut Session, id: u32) -> MenuResult {
+    let mut log = Vec::new();
+    let username = session.username().to_string();
+    let done = trade::cancel(&mut session.world.trades, &username, id, &mut log);
+    print_outcome(done, &log);
+    Ok(())
+}
+
+fn choose_partner_menu(session: &mut Session) -> MenuResult {
+    let mut menu = Menu::new(|_: &Session| println!("{}", text("trade.choose_partner")))
+        .back(text("menu.cancel"))
+        .once()
+        .saves(Session::save);
+    let partners: Vec<String> = session
+        .world
+        .players
+        .iter()
+        .map(|x| x.account.username.clone())
+        .filter(|x| x != session.username())
+        .collect();
+    for partner in partners {
+        menu = menu.item(MenuItem::new(
+            partner.clone(),
+            move |session: &mut Session| offer_menu(session, partner.clone()),
+        ));
     }
+    menu.run(session)
+}
 
-    // mine ores
-    player.mine_ore();
+// An offer being put together, nothing is checked until it is sent
+struct Offer<'a, 'b> {
+    session: &'a mut Session<'b>,
+    to: String,
+    give: Goods,
+    want: Goods,
+    sent: bool,
 }
 
-fn eat_food(player: &mut Player) {
-    loop {
-        println!("Your food sack: ");
-        player.inventory.print_food();
-        print!("Enter the index of the food you want to eat (0 to cancel): ");
-        unsafe_stdout_flush();
-
-        let mut input = String::new();
-        io::stdin().read_line(&mut input).unwrap();
-
-        let input = input.trim().parse::<i32>().unwrap_or(-1);
-        if input == 0 {
-            wait_for_enter();
-            return;
-        }
+impl Offer<'_, '_> {
+    fn save(&mut self) -> io::Result<()> {
+        self.session.save()
+    }
+}
 
-        if player.can_eat(input) {
-            player.eat(input);
-            println!("You regenerated some health!");
-        } else {
-            println!("Please choose a valid food!");
+fn offer_menu(session: &mut Session, to: String) -> MenuResult {
+    let mut offer = Offer {
+        session,
+        to,
+        give: Goods::default(),
+        want: Goods::default(),
+        sent: false,
+    };
+
+    Menu::new(|offer: &Offer| {
+        let (give, want) = (offer.give.describe(), offer.want.describe());
+        println!("{}", tr!("trade.draft", offer.to, give, want));
+        println!(
+            "{}",
+            tr!("shop.your_money", offer.session.player().account.money)
+        );
+    })
+    .item(
+        MenuItem::new(text("trade.give_money"), |offer: &mut Offer| {
+            offer.give.money = read_amount()?;
+            Ok(())
+        })
+        .hotkey('m'),
+    )
+    .item(MenuItem::new(text("trade.give_item"), give_item_menu).hotkey('i'))
+    .item(
+        MenuItem::new(text("trade.want_money"), |offer: &mut Offer| {
+            offer.want.money = read_amount()?;
+            Ok(())
+        })
+        .hotkey('n'),
+    )
+    .item(MenuItem::new(text("trade.want_item"), want_item_menu).hotkey('r'))
+    .item(
+        MenuItem::new(text("trade.send"), |offer: &mut Offer| {
+            let mut log = Vec::new();
+            let from = offer.session.username().to_string();
+            let world = &mut *offer.session.world;
+            offer.sent = trade::propose(
+                &world.players,
+                &mut world.trades,
+                &from,
+                &offer.to,
+                offer.give.clone(),
+                offer.want.clone(),
+                &mut log,
+            );
+            print_outcome(offer.sent, &log);
+            Ok(())
+        })
+        .hotkey('s')
+        .available_if(|offer| {
+            if offer.give.is_empty() && offer.want.is_empty() {
+                return Err(tr!("menu.empty_offer"));
+            }
+            afford(offer.session.player(), offer.give.money)
+        }),
+    )
+    .back(text("menu.cancel"))
+    .close_when(|offer| offer.sent)
+    .saves(Offer::save)
+    .run(&mut offer)
+}
+
+fn give_item_menu(offer: &mut Offer) -> MenuResult {
+    let mut menu = Menu::new(|_: &Offer| println!("{}", text("trade.give_item")))
+        .back(text("menu.cancel"))
+        .once()
+        .saves(Offer::save);
+    for item in Item::all() {
+        let owned = move |offer: &Offer| offer.session.player().inventory.count_item(item);
+        let label = move |offer: &Offer| tr!("trade.owned", item.name(), owned(offer));
+        let add = move |offer: &mut Offer| {
+            offer.give.add_item(item, 1);
+            Ok(())
+        };
+        menu = menu.item(MenuItem::labeled(label, add).available_if(move |offer| {
+            match owned(offer) > offer.give.count(item) {
+                true => Ok(()),
+                false => Err(tr!("menu.none_left")),
+            }
+        }));
+    }
+    menu.run(offer)
+}
+
+fn want_item_menu(offer: &mut Offer) -> MenuResult {
+    let mut menu = Menu::new(|_: &Offer| println!("{}", text("trade.want_item")))
+        .back(text("menu.cancel"))
+        .once()
+        .saves(Offer::save);
+    for item in Item::all() {
+        menu = menu.item(MenuItem::new(item.name(), move |offer: &mut Offer| {
+            offer.want.add_item(item, 1);
+            Ok(())
+        }));
+    }
+    menu.run(offer)
+}
+
+fn farm_menu(session: &mut Session) -> MenuResult {
+    Menu::new(|session: &Session| {
+        let clock = &session.world.info.clock;
+        println!("{}", clock.describe());
+        println!("{}", text("farm.plots"));
+        session.player().farm.print(clock);
+        println!("{}", tr!("shop.your_money", session.player().account.money));
+    })
+    .item(
+        MenuItem::new(text("farm.plant"), plant_menu)
+            .hotkey('p')
+            .available_if(|session| match session.player().farm.has_free_plot() {
+                true => Ok(()),
+                false => Err(tr!("menu.farm_full")),
+            }),
+    )
+    .item(
+        MenuItem::new(text("farm.harvest"), |session: &mut Session| {
+            let mut log = Vec::new();
+            let (player, clock) = session.player_and_clock();
+            let done = farm::harvest(player, clock, &mut log);
+            print_outcome(done, &log);
+            Ok(())
+        })
+        .hotkey('h')
+        .available_if(|session| {
+            match session.player().farm.has_ripe(&session.world.info.clock) {
+                true => Ok(()),
+                false => Err(tr!("menu.nothing_ripe")),
+            }
+        }),
+    )
+    .saves(Session::save)
+    .run(session)
+}
+
+fn plant_menu(session: &mut Session) -> MenuResult {
+    let mut menu = Menu::new(|_: &Session| println!("{}", text("farm.choose_seed")))
+        .back(text("menu.cancel"))
+        .once()
+        .saves(Session::save);
+    for crop in ALL_CROPS {
+        let label = tr!(
+            "farm.seed",
+            crop.name(),
+            crop.seed_price(),
+            crop.growth_minutes() / 60
+        );
+        let item = MenuItem::new(label, move |session: &mut Session| {
+            let mut log = Vec::new();
+            let (player, clock) = session.player_and_clock();
+            let done = farm::plant(player, clock, crop, &mut log);
+            print_outcome(done, &log);
+            Ok(())
+        })
+        .available_if(move |session| afford(session.player(), crop.seed_price()));
+        menu = menu.item(item);
+    }
+    menu.run(session)
+}
+
+fn bank_menu(session: &mut Session) -> MenuResult {
+    let mut log = Vec::new();
+    bank::pay_interest(session.player_mut(), &mut log);
+    print_log(&log);
+
+    Menu::new(|session: &Session| {
+        let player = session.player();
+        println!("{}", tr!("bank.vault", player.vault.goods.describe()));
+        println!(
+            "{}",
+            tr!("bank.slots", player.vault.used_slots(), VAULT_SLOTS)
+        );
+        println!("{}", tr!("shop.your_money", player.account.money));
+    })
+    .item(
+        MenuItem::new(text("bank.deposit_money"), |session: &mut Session| {
+            let goods = Goods {
+                money: read_amount()?,
+                items: Vec::new(),
+            };
+            let mut log = Vec::new();
+            let done = bank::deposit(session.player_mut(), goods, &mut log);
+            print_outcome(done, &log);
+            Ok(())
+        })
+        .hotkey('d')
+        .available_if(|session| match session.player().account.money {
+            Money::ZERO => Err(tr!("menu.no_money")),
+            _ => Ok(()),
+        }),
+    )
+    .item(MenuItem::new(text("bank.deposit_items"), deposit_items_menu).hotkey('i'))
+    .item(
+        MenuItem::new(text("bank.withdraw_money"), |session: &mut Session| {
+            let goods = Goods {
+                money: read_amount()?,
+                items: Vec::new(),
+            };
+            let mut log = Vec::new();
+            let done = bank::withdraw(session.player_mut(), goods, &mut log);
+            print_outcome(done, &log);
+            Ok(())
+        })
+        .hotkey('w')
+        .available_if(|session| match session.player().vault.goods.money {
+            Money::ZERO => Err(tr!("menu.no_money_in_vault")),
+            _ => Ok(()),
+        }),
+    )
+    .item(
+        MenuItem::new(text("bank.withdraw_items"), withdraw_items_menu)
+            .hotkey('t')
+            .available_if(
+                |session| match session.player().vault.goods.items.is_empty() {
+                    true => Err(tr!("menu.vault_empty")),
+                    false => Ok(()),
+                },
+            ),
+    )
+    .item(
+        MenuItem::new(text("bank.statement"), |session: &mut Session| {
+            statement(session.player())
+        })
+        .hotkey('s'),
+    )
+    .saves(Session::save)
+    .run(session)
+}
+
+// Asks how many and moves that many of the item in or out of the vault
+fn move_items(
+    session: &mut Session,
+    item: Item,
+    mover: fn(&mut Player, Goods, &mut Vec<String>) -> bool,
+) -> MenuResult {
+    let mut goods = Goods::default();
+    goods.add_item(item, read_amount()?);
+    let mut log = Vec::new();
+    let done = mover(session.player_mut(), goods, &mut log);
+    print_outcome(done, &log);
+    Ok(())
+}
+
+fn deposit_items_menu(session: &mut Session) -> MenuResult {
+    let mut menu = Menu::new(|_: &Session| println!("{}", text("bank.deposit_items")))
+        .back(text("menu.cancel"))
+        .once()
+        .saves(Session::save);
+    for item in Item::all() {
+        let owned = move |session: &Session| session.player().inventory.count_item(item);
+        let label = move |session: &Session| tr!("trade.owned", item.name(), owned(session));
+        let deposit = move |session: &mut Session| move_items(session, item, bank::deposit);
+        menu = menu.item(MenuItem::labeled(label, deposit).available_if(
+            move |session| match owned(session) {
+                0 => Err(tr!("menu.none_owned")),
+                _ => Ok(()),
+            },
+        ));
+    }
+    menu.run(session)
+}
+
+fn withdraw_items_menu(session: &mut Session) -> MenuResult {
+    let mut menu = Menu::new(|_: &Session| println!("{}", text("bank.withdraw_items")))
+        .back(text("menu.cancel"))
+        .once()
+        .saves(Session::save);
+    let stored: Vec<Item> = session
+        .player()
+        .vault
+        .goods
+        .items
+        .iter()
+        .map(|(item, _)| *item)
+        .collect();
+    for item in stored {
+        let label = move |session: &Session| {
+            tr!(
+                "bank.stored",
+                item.name(),
+                session.player().vault.goods.count(item)
+            )
+        };
+        let withdraw = move |session: &mut Session| move_items(session, item, bank::withdraw);
+        menu = menu.item(MenuItem::labeled(label, withdraw));
+    }
+    menu.run(session)
+}
+
+fn statement(player: &Player) -> MenuResult {
+    println!("{}", text("bank.statement"));
+    if player.vault.statement.is_empty() {
+        println!("{}", text("bank.statement_empty"));
+    }
+    for entry in &player.vault.statement {
+        let key = match entry.kind {
+            EntryKind::Deposit => "bank.entry.deposit",
+            EntryKind::Withdrawal => "bank.entry.withdrawal",
+            EntryKind::Interest => "bank.entry.interest",
+        };
+        println!(
+            "{}",
+            tr!(key, format_time(entry.time), entry.goods.describe())
+        );
+    }
+    wait_for_enter()
+}
+
+fn auction_menu(session: &mut Session) -> MenuResult {
+    Menu::new(|session: &Session| {
+        let house = &session.world.auctions;
+        println!("{}", text("auction.listings"));
+        if house.listings.is_empty() {
+            println!("- {}", text("common.none"));
         }
-        wait_for_enter();
+        for listing in &house.listings {
+            println!("- {}", describe_listing(house, listing));
+        }
+        println!("{}", tr!("shop.your_money", session.player().account.money));
+    })
+    .item(
+        MenuItem::new(text("auction.place_bid"), |session: &mut Session| {
+            let ids = biddable(session);
+            choose_listing_menu(session, ids, place_bid)
+        })
+        .hotkey('p')
+        .available_if(|session| match biddable(session).is_empty() {
+            true => Err(tr!("menu.no_listings")),
+            false => Ok(()),
+        }),
+    )
+    .item(MenuItem::new(text("auction.list_item"), list_item_menu).hotkey('l'))
+    .item(
+        MenuItem::new(text("auction.take_back"), |session: &mut Session| {
+            let ids = withdrawable(session);
+            choose_listing_menu(session, ids, take_back_listing)
+        })
+        .hotkey('w')
+        .available_if(|session| match withdrawable(session).is_empty() {
+            true => Err(tr!("menu.no_own_listings")),
+            false => Ok(()),
+        }),
+    )
+    .saves(Session::save)
+    .run(session)
+}
+
+fn describe_listing(house: &AuctionHouse, listing: &Listing) -> String {
+    let price = match &listing.bid {
+        Some(bid) => tr!("auction.highest_bid", bid.amount, bid.bidder),
+        None => tr!("auction.starts_at", listing.start_price),
+    };
+    tr!(
+        "auction.entry",
+        listing.id,
+        listing.describe(),
+        listing.seller,
+        price,
+        listing.turns_left(house)
+    )
+}
+
+// Listings of the others
+fn biddable(session: &Session) -> Vec<u32> {
+    let listings = &session.world.auctions.listings;
+    listings
+        .iter()
+        .filter(|listing| listing.seller != session.username())
+        .map(|listing| listing.id)
+        .collect()
+}
+
+// The player's own listings nobody bid on
+fn withdrawable(session: &Session) -> Vec<u32> {
+    let house = &session.world.auctions;
+    house
+        .listings_of(session.username())
+        .filter(|listing| listing.bid.is_none())
+        .map(|listing| listing.id)
+        .collect()
+}
+
+fn choose_listing_menu(
+    session: &mut Session,
+    ids: Vec<u32>,
+    action: fn(&mut Session, u32) -> MenuResult,
+) -> MenuResult {
+    let mut menu = Menu::new(|_: &Session| println!("{}", text("auction.choose_listing")))
+        .back(text("menu.cancel"))
+        .once()
+        .saves(Session::save);
+    for id in ids {
+        let label = move |session: &Session| {
+            let house = &session.world.auctions;
+            match house.listings.iter().find(|x| x.id == id) {
+                Some(listing) => describe_listing(house, listing),
+                None => text("item.empty").to_string(),
+            }
+        };
+        menu = menu.item(MenuItem::labeled(label, move |session: &mut Session| {
+            action(session, id)
+        }));
+    }
+    menu.run(session)
+}
+
+fn place_bid(session: &mut Session, id: u32) -> MenuResult {
+    let listings = &session.world.auctions.listings;
+    if let Some(listing) = listings.iter().find(|x| x.id == id) {
+        println!("{}", tr!("auction.minimum_bid", listing.minimum_bid()));
+    }
+    let amount = read_amount()?;
+
+    let mut log = Vec::new();
+    let username = session.username().to_string();
+    let world = &mut *session.world;
+    let done = auction::bid(
+        &mut world.players,
+        &mut world.auctions,
+        &username,
+        id,
+        amount,
+        &mut log,
+    );
+    print_outcome(done, &log);
+    Ok(())
+}
+
+fn take_back_listing(session: &mut Session, id: u32) -> MenuResult {
+    let mut log = Vec::new();
+    let username = session.username().to_string();
+    let world = &mut *session.world;
+    let done = auction::withdraw(
+        &mut world.players,
+        &mut world.auctions,
+        &username,
+        id,
+        &mut log,
+    );
+    print_outcome(done, &log);
+    Ok(())
+}
+
+fn list_item_menu(session: &mut Session) -> MenuResult {
+    let mut menu = Menu::new(|_: &Session| println!("{}", text("auction.choose_item")))
+        .back(text("menu.cancel"))
+        .once()
+        .saves(Session::save);
+    for item in Item::all() {
+        let owned = move |session: &Session| session.player().inventory.count_item(item);
+        let label = move |session: &Session| tr!("trade.owned", item.name(), owned(session));
+        let list = move |session: &mut Session| {
+            let count = read_amount()?;
+            let price = read_number(text("auction.price_prompt"))?;
+            let duration = read_number(&tr!("auction.duration_prompt", MAX_DURATION))?;
+
+            let mut log = Vec::new();
+            let world = &mut *session.world;
+            let done = auction::list(
+                &mut world.players[session.index],
+                &mut world.auctions,
+                item,
+                count,
+                price,
+                duration,
+                &mut log,
+            );
+            print_outcome(done, &log);
+            Ok(())
+        };
+        menu = menu.item(MenuItem::labeled(label, list).available_if(move |session| {
+            match owned(session) {
+                0 => Err(tr!("menu.none_owned")),
+                _ => Ok(()),
+            }
+        }));
     }
+    menu.run(session)
+}
+
+fn achievements_menu(player: &Player) -> MenuResult {
+    println!("{}", text("achievement.title"));
+    player.achievements.print_progress();
+    wait_for_enter()
 }
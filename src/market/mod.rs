@@ -0,0 +1,113 @@
+use crate::inventory::{Ore, ALL_ORES};
+use crate::money::Money;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::fs::File;
+use std::io::{self, BufRead, Write};
+
+// Ore prices are a percentage of the base price, shared by everyone in a
+// world. Every ore sold pushes its price down a little and prices drift
+// back to normal each time the world is opened again.
+const MIN_PERCENT: u32 = 50;
+const NORMAL_PERCENT: u32 = 100;
+const RECOVERY_PERCENT: u32 = 10;
+
+pub struct Market {
+    pub coal: u32,
+    pub iron: u32,
+    pub gold: u32,
+    pub diamond: u32,
+}
+
+impl Market {
+    // A new world starts with prices a bit above or below normal
+    pub fn new(seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        Market {
+            iron: rng.gen_range(90..=110),
+            gold: rng.gen_range(90..=110),
+            diamond: rng.gen_range(90..=110),
+            // drawn last so older seeds keep their other prices
+            coal: rng.gen_range(90..=110),
+        }
+    }
+
+    fn percent_mut(&mut self, ore: Ore) -> &mut u32 {
+        match ore {
+            Ore::Coal => &mut self.coal,
+            Ore::IronOre => &mut self.iron,
+            Ore::GoldOre => &mut self.gold,
+            Ore::Diamond => &mut self.diamond,
+        }
+    }
+
+    pub fn percent(&self, ore: Ore) -> u32 {
+        match ore {
+            Ore::Coal => self.coal,
+            Ore::IronOre => self.iron,
+            Ore::GoldOre => self.gold,
+            Ore::Diamond => self.diamond,
+        }
+    }
+
+    pub fn price(&self, ore: Ore) -> Money {
+        let base = ore.price().get() as u64;
+        let price = (base * self.percent(ore) as u64 / 100).clamp(1, u32::MAX as u64);
+        Money::new(price as u32)
+    }
+
+    // Returns what the ore sold for
+    pub fn sell(&mut self, ore: Ore) -> Money {
+        let price = self.price(ore);
+        let percent = self.percent_mut(ore);
+        *percent = percent.saturating_sub(1).max(MIN_PERCENT);
+        price
+    }
+
+    pub fn recover(&mut self) {
+        for ore in ALL_ORES {
+            let percent = self.percent_mut(ore);
+            *percent = if *percent < NORMAL_PERCENT {
+                (*percent + RECOVERY_PERCENT).min(NORMAL_PERCENT)
+            } else {
+                percent.saturating_sub(RECOVERY_PERCENT).max(NORMAL_PERCENT)
+            };
+        }
+    }
+}
+
+fn parse_market_string(market_string: &str, market: &mut Market) {
+    for token in market_string.split(';') {
+        let Some((name, percent)) = token.split_once(':') else {
+            continue;
+        };
+        if let (Some(ore), Ok(percent)) = (Ore::from_string(name), percent.parse()) {
+            *market.percent_mut(ore) = percent;
+        }
+    }
+}
+
+// The seed is only used when the world has no market file yet
+pub fn load_market_file(file_path: &str, seed: u64) -> io::Result<Market> {
+    let mut market = Market::new(seed);
+    let file = File::open(file_path)?;
+    let reader = io::BufReader::new(file);
+
+    for line_content in reader.lines().map_while(Result::ok) {
+        parse_market_string(&line_content, &mut market);
+    }
+
+    Ok(market)
+}
+
+pub fn update_market_file(market: &Market, file_path: &str) -> io::Result<()> {
+    let mut file = File::create(file_path)?;
+
+    let mut market_string = String::new();
+    for ore in ALL_ORES {
+        market_string.push_str(&format!("{}:{};", ore.to_string(), market.percent(ore)));
+    }
+
+    market_string.push('\n');
+    file.write_all(market_string.as_bytes())
+}
@@ -0,0 +1,249 @@
+// Numbered menus for the line based frontend. Options are declared once
+// with a label, an optional hotkey, a check telling whether they can be
+// picked right now and the action to run, numbering is done here.
+use crate::input::{ask_quit, invalid_choice, read_line, unsafe_stdout_flush, Exit};
+use crate::lang::{text, tr};
+use crate::style;
+use std::io;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+// What every menu returns, an Err means the game should end right away
+pub type MenuResult = Result<(), Exit>;
+
+// Every menu ends with a way back, picked by its number or this key
+const BACK_HOTKEY: char = 'b';
+
+// Actions taken in menus that can save, counted across all of them
+static ACTIONS_SINCE_SAVE: AtomicU32 = AtomicU32::new(0);
+// Autosave after this many actions, 0 turns autosaving off
+static AUTOSAVE_EVERY: AtomicU32 = AtomicU32::new(20);
+
+// Err holds the reason the item can't be picked
+type Availability<'a, S> = Box<dyn Fn(&S) -> Result<(), String> + 'a>;
+type Save<'a, S> = Box<dyn FnMut(&mut S) -> io::Result<()> + 'a>;
+type Done<'a, S> = Box<dyn Fn(&S) -> bool + 'a>;
+
+pub fn set_autosave(every: u32) {
+    AUTOSAVE_EVERY.store(every, Ordering::SeqCst);
+}
+
+pub struct MenuItem<'a, S: ?Sized> {
+    label: Box<dyn Fn(&S) -> String + 'a>,
+    hotkey: Option<char>,
+    available: Availability<'a, S>,
+    action: Box<dyn FnMut(&mut S) -> MenuResult + 'a>,
+}
+
+impl<'a, S: ?Sized> MenuItem<'a, S> {
+    pub fn new(label: impl Into<String>, action: impl FnMut(&mut S) -> MenuResult + 'a) -> Self {
+        let label = label.into();
+        Self::labeled(move |_| label.clone(), action)
+    }
+
+    // For labels that change while the menu is open, like prices
+    pub fn labeled(
+        label: impl Fn(&S) -> String + 'a,
+        action: impl FnMut(&mut S) -> MenuResult + 'a,
+    ) -> Self {
+        MenuItem {
+            label: Box::new(label),
+            hotkey: None,
+            available: Box::new(|_| Ok(())),
+            action: Box::new(action),
+        }
+    }
+
+    pub fn hotkey(mut self, hotkey: char) -> Self {
+        self.hotkey = Some(hotkey);
+        self
+    }
+
+    pub fn available_if(mut self, available: impl Fn(&S) -> Result<(), String> + 'a) -> Self {
+        self.available = Box::new(available);
+        self
+    }
+
+    fn describe(&self, number: usize, state: &S) -> String {
+        let mut line = format!("{}. {}", number, (self.label)(state));
+        if let Some(hotkey) = self.hotkey {
+            line.push_str(&format!(" [{}]", style::bold(&hotkey.to_string())));
+        }
+        if let Err(reason) = (self.available)(state) {
+            line.push(' ');
+            line.push_str(&tr!("menu.unavailable", reason));
+            return style::dim(&line);
+        }
+        line
+    }
+}
+
+enum Choice {
+    Item(usize),
+    Back,
+    Save,
+    Quit,
+}
+
+pub struct Menu<'a, S: ?Sized> {
+    header: Box<dyn Fn(&S) + 'a>,
+    items: Vec<MenuItem<'a, S>>,
+    back_label: &'a str,
+    once: bool,
+    done: Option<Done<'a, S>>,
+    save: Option<Save<'a, S>>,
+}
+
+impl<'a, S: ?Sized> Menu<'a, S> {
+    // The header is printed above the options every time they are shown
+    pub fn new(header: impl Fn(&S) + 'a) -> Self {
+        Menu {
+            header: Box::new(header),
+            items: Vec::new(),
+            back_label: text("menu.back"),
+            once: false,
+            done: None,
+            save: None,
+        }
+    }
+
+    pub fn item(mut self, item: MenuItem<'a, S>) -> Self {
+        self.items.push(item);
+        self
+    }
+
+    pub fn back(mut self, label: &'a str) -> Self {
+        self.back_label = label;
+        self
+    }
+
+    // Leave the menu after the first action instead of showing it again
+    pub fn once(mut self) -> Self {
+        self.once = true;
+        self
+    }
+
+    // Leave the menu as soon as an action makes this true
+    pub fn close_when(mut self, done: impl Fn(&S) -> bool + 'a) -> Self {
+        self.done = Some(Box::new(done));
+        self
+    }
+
+    // Offers the save command in this menu and autosaves after its actions
+    pub fn saves(mut self, save: impl FnMut(&mut S) -> io::Result<()> + 'a) -> Self {
+        self.save = Some(Box::new(save));
+        self
+    }
+
+    fn save(&mut self, state: &mut S, message: &str) {
+        let Some(save) = &mut self.save else {
+            return;
+        };
+        match save(state) {
+            Ok(()) => {
+                ACTIONS_SINCE_SAVE.store(0, Ordering::SeqCst);
+                println!("{}", message);
+            }
+            Err(e) => println!("{}", style::error(&tr!("main.save_failed", e))),
+        }
+    }
+
+    fn autosave(&mut self, state: &mut S) {
+        if self.save.is_none() {
+            return;
+        }
+        let every = AUTOSAVE_EVERY.load(Ordering::SeqCst);
+        let actions = ACTIONS_SINCE_SAVE.fetch_add(1, Ordering::SeqCst) + 1;
+        if every > 0 && actions >= every {
+            self.save(state, &style::dim(text("menu.autosaved")));
+        }
+    }
+
+    pub fn run(&mut self, state: &mut S) -> MenuResult {
+        loop {
+            (self.header)(state);
+            for (i, item) in self.items.iter().enumerate() {
+                println!("{}", item.describe(i + 1, state));
+            }
+            println!(
+                "{}. {} [{}]",
+                self.items.len() + 1,
+                self.back_label,
+                style::bold(&BACK_HOTKEY.to_string())
+            );
+            let commands = match self.save {
+                Some(_) => tr!(
+                    "menu.commands_save",
+                    text("menu.save_word"),
+                    text("menu.quit_word")
+                ),
+                None => tr!("menu.commands", text("menu.quit_word")),
+            };
+            println!("{}", style::dim(&commands));
+            print!(">> ");
+            unsafe_stdout_flush();
+
+            let index = match self.choose(&read_line()?) {
+                Some(Choice::Item(index)) => index,
+                Some(Choice::Back) => return Ok(()),
+                Some(Choice::Save) => {
+                    self.save(state, text("menu.saved"));
+                    continue;
+                }
+                Some(Choice::Quit) => {
+                    ask_quit()?;
+                    continue;
+                }
+                None => {
+                    invalid_choice();
+                    continue;
+                }
+            };
+
+            let item = &mut self.items[index];
+            if let Err(reason) = (item.available)(state) {
+                println!(
+                    "{}",
+                    style::error(&tr!("menu.unavailable_choice", (item.label)(state), reason))
+                );
+                continue;
+            }
+            (item.action)(state)?;
+            self.autosave(state);
+
+            if self.once || self.done.as_ref().is_some_and(|done| done(state)) {
+                return Ok(());
+            }
+        }
+    }
+
+    fn choose(&self, input: &str) -> Option<Choice> {
+        let input = input.to_lowercase();
+        if input == text("menu.save_word") && self.save.is_some() {
+            return Some(Choice::Save);
+        }
+        if input == text("menu.quit_word") {
+            return Some(Choice::Quit);
+        }
+        if let Ok(number) = input.parse::<usize>() {
+            return match number {
+                0 => None,
+                n if n <= self.items.len() => Some(Choice::Item(n - 1)),
+                n if n == self.items.len() + 1 => Some(Choice::Back),
+                _ => None,
+            };
+        }
+
+        let mut chars = input.chars();
+        let hotkey = chars.next()?;
+        if chars.next().is_some() {
+            return None;
+        }
+        if hotkey == BACK_HOTKEY {
+            return Some(Choice::Back);
+        }
+        self.items
+            .iter()
+            .position(|item| item.hotkey == Some(hotkey))
+            .map(Choice::Item)
+    }
+}
@@ -0,0 +1,322 @@
+use crate::inventory::Ore;
+use crate::player::Player;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{self, BufRead, Write};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Direction {
+    North,
+    South,
+    East,
+    West,
+    Down,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Tile {
+    Rock,
+    Bedrock,
+    Ore(Ore),
+    Lava,
+    Water,
+    Creeper,
+    Spider,
+}
+
+// A tile is identified by (x, y, depth)
+pub type Position = (i32, i32, u8);
+
+pub const ENTRANCE: Position = (0, 0, 1);
+
+// How many tiles around the player are drawn on the map
+pub const MAP_RADIUS: i32 = 4;
+
+// Glyphs with the catalog key of their name
+pub const MAP_LEGEND: [(char, &str); 11] = [
+    ('@', "legend.you"),
+    ('.', "legend.tunnel"),
+    ('#', "legend.rock"),
+    ('X', "legend.bedrock"),
+    ('c', "ore.coal"),
+    ('i', "ore.iron_ore"),
+    ('g', "ore.gold_ore"),
+    ('d', "ore.diamond"),
+    ('^', "legend.lava"),
+    ('~', "legend.water"),
+    (' ', "legend.unexplored"),
+];
+
+// The mine is generated from the seed, so only the tiles the player has
+// already dug out need to be remembered
+pub struct MineMap {
+    pub seed: u64,
+    pub x: i32,
+    pub y: i32,
+    pub depth: u8,
+    mined: HashSet<Position>,
+}
+
+impl Direction {
+    fn offset(self) -> (i32, i32, u8) {
+        match self {
+            Direction::North => (0, -1, 0),
+            Direction::South => (0, 1, 0),
+            Direction::East => (1, 0, 0),
+            Direction::West => (-1, 0, 0),
+            Direction::Down => (0, 0, 1),
+        }
+    }
+}
+
+impl Tile {
+    // Creatures hide inside the rock until they're dug out
+    pub fn glyph(self) -> char {
+        match self {
+            Tile::Rock | Tile::Creeper | Tile::Spider => '#',
+            Tile::Bedrock => 'X',
+            Tile::Ore(Ore::Coal) => 'c',
+            Tile::Ore(Ore::IronOre) => 'i',
+            Tile::Ore(Ore::GoldOre) => 'g',
+            Tile::Ore(Ore::Diamond) => 'd',
+            Tile::Lava => '^',
+            Tile::Water => '~',
+        }
+    }
+}
+
+impl MineMap {
+    pub fn new(seed: u64) -> Self {
+        let mut mined = HashSet::new();
+        mined.insert(ENTRANCE);
+        let (x, y, depth) = ENTRANCE;
+        MineMap {
+            seed,
+            x,
+            y,
+            depth,
+            mined,
+        }
+    }
+
+    pub fn generate() -> Self {
+        MineMap::new(rand::random())
+    }
+
+    pub fn position(&self) -> Position {
+        (self.x, self.y, self.depth)
+    }
+
+    pub fn return_to_entrance(&mut self) {
+        (self.x, self.y, self.depth) = ENTRANCE;
+    }
+
+    pub fn is_mined(&self, position: Position) -> bool {
+        self.mined.contains(&position)
+    }
+
+    // What was originally at this position, before the player dug it out
+    pub fn tile_at(&self, position: Position) -> Tile {
+        if position == ENTRANCE {
+            return Tile::Rock;
+        }
+
+        let (x, y, depth) = position;
+        let mut rng = StdRng::seed_from_u64(tile_seed(self.seed, position));
+        let depth_bonus = (depth / 4).min(10) as i32;
+        let roll: i32 = rng.gen_range(1..=100);
+
+        // keep the tiles next to the entrance open so the player can't get stuck
+        let near_entrance = depth == 1 && x.abs() + y.abs() <= 1;
+        if roll <= 10 && !near_entrance {
+            Tile::Bedrock
+        } else if roll <= 45 {
+            let ore_roll: i32 = rng.gen_range(1..=100);
+            if ore_roll <= 5 + depth_bonus * 2 {
+                Tile::Ore(Ore::Diamond)
+            } else if ore_roll <= 30 + depth_bonus * 3 {
+                Tile::Ore(Ore::GoldOre)
+            } else if ore_roll > 70 + depth_bonus * 2 {
+                // coal gets scarce the deeper you go
+                Tile::Ore(Ore::Coal)
+            } else {
+                Tile::Ore(Ore::IronOre)
+            }
+        } else if roll <= 49 + depth_bonus {
+            Tile::Lava
+        } else if roll <= 57 + depth_bonus {
+            Tile::Creeper
+        } else if roll <= 64 + depth_bonus {
+            Tile::Spider
+        } else if roll <= 70 + depth_bonus {
+            Tile::Water
+        } else {
+            Tile::Rock
+        }
+    }
+
+    // None past the edge of the mine, the deepest level is the last a u8 holds
+    fn neighbour(&self, direction: Direction) -> Option<Position> {
+        let (dx, dy, dz) = direction.offset();
+        Some((
+            self.x.checked_add(dx)?,
+            self.y.checked_add(dy)?,
+            self.depth.checked_add(dz)?,
+        ))
+    }
+
+    pub fn can_move(&self, direction: Direction) -> bool {
+        match self.neighbour(direction) {
+            Some(position) => self.is_mined(position) || self.tile_at(position) != Tile::Bedrock,
+            None => false,
+        }
+    }
+
+    // A tile can be seen once it's dug out or right next to a dug out tile
+    pub fn is_explored(&self, position: Position) -> bool {
+        let (x, y, depth) = position;
+        self.is_mined(position)
+            || [(0, -1), (0, 1), (1, 0), (-1, 0)]
+                .iter()
+                .any(|(dx, dy)| self.is_mined((x + dx, y + dy, depth)))
+    }
+
+    // Draws the explored area around the player on the current depth,
+    // one string per row from north to south
+    pub fn render(&self, radius: i32) -> Vec<String> {
+        let mut rows = Vec::new();
+        for y in self.y - radius..=self.y + radius {
+            let mut row = String::new();
+            for x in self.x - radius..=self.x + radius {
+                let position = (x, y, self.depth);
+                let glyph = if (x, y) == (self.x, self.y) {
+                    '@'
+                } else if self.is_mined(position) {
+                    '.'
+                } else if self.is_explored(position) {
+                    self.tile_at(position).glyph()
+                } else {
+                    ' '
+                };
+                row.push(glyph);
+            }
+            rows.push(row);
+        }
+        rows
+    }
+
+    // Moves the player, returning the tile that got dug out if the player
+    // broke new ground
+    pub fn step(&mut self, direction: Direction) -> Option<Tile> {
+        if !self.can_move(direction) {
+            return None;
+        }
+        let position = self.neighbour(direction)?;
+        (self.x, self.y, self.depth) = position;
+
+        if self.mined.insert(position) {
+            Some(self.tile_at(position))
+        } else {
+            None
+        }
+    }
+}
+
+// Mixes the world seed and the position into a seed for a single tile
+fn tile_seed(seed: u64, position: Position) -> u64 {
+    let (x, y, depth) = position;
+    let mut hash = seed;
+    for value in [x as u32 as u64, y as u32 as u64, depth as u64] {
+        hash ^= value.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        hash = (hash ^ (hash >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        hash = (hash ^ (hash >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        hash ^= hash >> 31;
+    }
+    hash
+}
+
+// `x,y,depth`
+fn parse_position(token: &str) -> Option<Position> {
+    let coordinates: Vec<&str> = token.split(',').collect();
+    if coordinates.len() != 3 {
+        return None;
+    }
+    Some((
+        coordinates[0].parse().ok()?,
+        coordinates[1].parse().ok()?,
+        coordinates[2].parse().ok()?,
+    ))
+}
+
+// `username;seed;@x,y,depth;x,y,depth;...`, the position of the player
+// first and then every tile that was dug out
+fn parse_mine_string(mine_string: &str) -> Result<MineMap, &'static str> {
+    // first token is the username
+    let mut tokens = mine_string.split(';').skip(1);
+    let seed = tokens
+        .next()
+        .and_then(|seed| seed.trim().parse().ok())
+        .ok_or("Invalid seed")?;
+
+    let mut mine = MineMap::new(seed);
+    let mut position = ENTRANCE;
+    for token in tokens {
+        match token.strip_prefix('@') {
+            Some(token) => position = parse_position(token).unwrap_or(ENTRANCE),
+            None => {
+                if let Some(tile) = parse_position(token) {
+                    mine.mined.insert(tile);
+                }
+            }
+        }
+    }
+    // saves from before the position was kept, or broken ones, start at the
+    // entrance
+    if mine.is_mined(position) {
+        (mine.x, mine.y, mine.depth) = position;
+    }
+
+    Ok(mine)
+}
+
+pub fn search_mine_file(file_path: &str, username: &str) -> Result<MineMap, io::Error> {
+    let file = File::open(file_path)?;
+    let reader = io::BufReader::new(file);
+
+    for line_content in reader.lines().map_while(Result::ok) {
+        if line_content.split(';').next() != Some(username) {
+            continue;
+        }
+
+        if let Ok(mine) = parse_mine_string(&line_content) {
+            return Ok(mine);
+        }
+    }
+
+    Ok(MineMap::generate())
+}
+
+pub fn update_mine_file(players: &[Player], file_path: &str) -> io::Result<()> {
+    let mut file = File::create(file_path)?;
+
+    for player in players {
+        let mine = &player.mine;
+        let mut mine_string = format!(
+            "{};{};@{},{},{};",
+            player.account.username, mine.seed, mine.x, mine.y, mine.depth
+        );
+
+        let mut mined: Vec<&Position> = mine.mined.iter().collect();
+        mined.sort();
+        for (x, y, depth) in mined {
+            mine_string.push_str(&format!("{},{},{};", x, y, depth));
+        }
+
+        mine_string.push('\n');
+        file.write_all(mine_string.as_bytes())?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,105 @@
+// An amount of money. Adding saturates at the largest amount instead of
+// wrapping around, and there is no plain subtraction: taking money away
+// either goes through `checked_sub` or stops at zero.
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Mul};
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Money(u32);
+
+impl Money {
+    pub const ZERO: Money = Money(0);
+    pub const MAX: Money = Money(u32::MAX);
+
+    pub const fn new(amount: u32) -> Money {
+        Money(amount)
+    }
+
+    pub fn get(self) -> u32 {
+        self.0
+    }
+
+    pub fn is_zero(self) -> bool {
+        self.0 == 0
+    }
+
+    // None when there isn't enough
+    pub fn checked_sub(self, other: Money) -> Option<Money> {
+        self.0.checked_sub(other.0).map(Money)
+    }
+
+    pub fn saturating_sub(self, other: Money) -> Money {
+        Money(self.0.saturating_sub(other.0))
+    }
+}
+
+impl Add for Money {
+    type Output = Money;
+
+    fn add(self, other: Money) -> Money {
+        Money(self.0.saturating_add(other.0))
+    }
+}
+
+impl AddAssign for Money {
+    fn add_assign(&mut self, other: Money) {
+        *self = *self + other;
+    }
+}
+
+impl Mul<u32> for Money {
+    type Output = Money;
+
+    fn mul(self, times: u32) -> Money {
+        Money(self.0.saturating_mul(times))
+    }
+}
+
+impl Sum for Money {
+    fn sum<I: Iterator<Item = Money>>(iter: I) -> Money {
+        iter.fold(Money::ZERO, Add::add)
+    }
+}
+
+// Just the number, the catalogs put the `$` in front
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl FromStr for Money {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(string: &str) -> Result<Money, Self::Err> {
+        string.parse().map(Money)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adding_stops_at_the_largest_amount() {
+        assert_eq!(Money::MAX + Money::new(1), Money::MAX);
+        assert_eq!(Money::new(u32::MAX - 1) * 2, Money::MAX);
+        assert_eq!(
+            [Money::MAX, Money::MAX].into_iter().sum::<Money>(),
+            Money::MAX
+        );
+
+        let mut money = Money::MAX;
+        money += Money::new(5);
+        assert_eq!(money, Money::MAX);
+    }
+
+    #[test]
+    fn taking_away_never_goes_below_zero() {
+        assert_eq!(Money::new(5).checked_sub(Money::new(6)), None);
+        assert_eq!(Money::new(5).checked_sub(Money::new(5)), Some(Money::ZERO));
+        assert_eq!(Money::new(5).saturating_sub(Money::new(6)), Money::ZERO);
+    }
+}
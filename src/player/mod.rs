@@ -1,13 +1,40 @@
 use crate::account::*;
+use crate::achievement::Achievements;
+use crate::bank::Vault;
+use crate::effect::{EffectKind, EffectTick, StatusEffects};
+use crate::equipment::{DamageKind, Equipment};
+use crate::expedition::Expedition;
+use crate::farm::Farm;
 use crate::inventory::*;
-use rand::Rng;
+use crate::lang::text;
+use crate::mine::{MineMap, ENTRANCE};
+use crate::money::Money;
+use crate::quest::QuestLog;
+use crate::stat::Stat;
 use std::cmp::Ordering;
+use std::fs::File;
+use std::io::{self, BufRead, Write};
+
+pub const MAX_HEALTH: u8 = 100;
+pub const MAX_HUNGER: u8 = 100;
+const STARVING_DAMAGE: u8 = 10;
+
+pub type Health = Stat<MAX_HEALTH>;
+pub type Hunger = Stat<MAX_HUNGER>;
 
 pub struct Player {
     pub account: Account,
     pub inventory: Inventory,
-    health: u8,
-    depth: u8,
+    pub achievements: Achievements,
+    pub quests: QuestLog,
+    pub effects: StatusEffects,
+    pub equipment: Equipment,
+    pub mine: MineMap,
+    pub vault: Vault,
+    pub farm: Farm,
+    pub expedition: Expedition,
+    health: Health,
+    hunger: Hunger,
 }
 
 impl Eq for Player {}
@@ -20,7 +47,7 @@ impl PartialEq for Player {
 
 impl PartialOrd for Player {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        self.account.money.partial_cmp(&other.account.money)
+        Some(self.cmp(other))
     }
 }
 
@@ -31,101 +58,150 @@ impl Ord for Player {
 }
 
 impl Player {
-    pub fn spend(&mut self, amount: u32) -> Result<(), &'static str> {
-        if amount <= self.account.money {
-            self.account.money -= amount;
-            Ok(())
-        } else {
-            Err("Error: Not enough money!")
+    pub fn spend(&mut self, amount: Money) -> Result<(), &'static str> {
+        match self.account.money.checked_sub(amount) {
+            Some(left) => {
+                self.account.money = left;
+                Ok(())
+            }
+            None => Err(text("error.not_enough_money")),
         }
     }
 
     pub fn take_damage(&mut self, damage: u8) {
-        let (new_health, overflowed) = self.health.overflowing_sub(damage);
+        let before = self.health.get();
+        self.health.lower(damage);
+        self.expedition.add_damage(before - self.health.get());
+    }
 
-        if overflowed {
-            self.health = 0;
-        } else {
-            self.health = new_health;
-        }
+    // Damage that goes through the player's equipment first.
+    // Returns how much damage was actually taken.
+    pub fn take_hit(&mut self, damage: u8, kind: DamageKind) -> u8 {
+        let damage = self.equipment.mitigate(damage, kind);
+        self.take_damage(damage);
+        damage
     }
 
     pub fn heal(&mut self, heal: u8) {
-        self.health += heal;
-
-        if self.health > 100 {
-            self.health = 100;
-            return;
-        }
+        self.health.raise(heal);
     }
 
     pub fn is_alive(&self) -> bool {
-        self.health > 0
+        !self.health.is_empty()
     }
 
     pub fn get_health(&self) -> u8 {
-        self.health
+        self.health.get()
+    }
+
+    pub fn get_hunger(&self) -> u8 {
+        self.hunger.get()
+    }
+
+    pub fn is_starving(&self) -> bool {
+        self.hunger.is_empty()
+    }
+
+    pub fn satiate(&mut self, saturation: u8) {
+        self.hunger.raise(saturation);
+    }
+
+    // Digging makes you hungry, more so the deeper you are.
+    // Returns true if the player took damage from starving.
+    pub fn burn_hunger(&mut self) -> bool {
+        if self.is_starving() {
+            self.take_hit(STARVING_DAMAGE, DamageKind::Starvation);
+            return true;
+        }
+
+        let drain = 2 + self.get_depth() / 5;
+        self.hunger.lower(drain);
+        false
+    }
+
+    // Advances every status effect by one turn and applies what they did
+    pub fn tick_effects(&mut self) -> EffectTick {
+        let tick = self.effects.tick();
+        self.take_damage(tick.damage);
+        self.heal(tick.heal);
+        tick
     }
 
     pub fn new(inventory: Inventory, account: Account) -> Player {
         Player {
             account,
             inventory,
-            health: 100,
-            depth: 1,
+            achievements: Achievements::create_empty(),
+            quests: QuestLog::create_empty(),
+            effects: StatusEffects::create_empty(),
+            equipment: Equipment::create_empty(),
+            mine: MineMap::generate(),
+            vault: Vault::create_empty(),
+            farm: Farm::create_empty(),
+            expedition: Expedition::create_empty(),
+            health: Health::full(),
+            hunger: Hunger::full(),
         }
     }
 
     pub fn stop_mining(&mut self) {
-        self.depth = 1;
+        self.mine.return_to_entrance();
     }
 
-    pub fn get_depth(&self) -> u8 {
-        self.depth
+    // Anywhere but the entrance, where the way to town is
+    pub fn is_in_mine(&self) -> bool {
+        self.mine.position() != ENTRANCE
     }
 
-    pub fn go_deeper(&mut self) {
-        self.depth += 1;
+    pub fn get_depth(&self) -> u8 {
+        self.mine.depth
     }
 
-    pub fn mine_ore(&mut self) {
-        let (iron_chance, gold_chance) = match self.account.pickaxe_level {
-            3 => (60, 40),
-            2 => (54, 36),
-            1 | _ => (57, 28),
+    // Mines the ore out of a tile, better pickaxes get more out of it.
+    // Returns the ores that were mined
+    pub fn mine_ore(&mut self, ore: Ore) -> (Vec<Ore>, u32) {
+        let mut amount = match self.account.pickaxe_level {
+            3 => 2,
+            2 if rand::random() => 2,
+            _ => 1,
         };
+        if self.effects.has(EffectKind::Haste) {
+            amount += 1;
+        }
 
-        for _ in 1..3 {
-            if rand::random() {
-                self.inventory.push_ore(mine(iron_chance, gold_chance));
+        let mut mined = Vec::new();
+        let mut lost = 0;
+        for _ in 0..amount {
+            match self.inventory.push_ore(ore) {
+                true => mined.push(ore),
+                false => lost += 1,
             }
         }
+        (mined, lost)
     }
 
     pub fn can_eat(&self, index: i32) -> bool {
-        if !(index >= 1 && index <= 6) {
+        if !(1..=6).contains(&index) {
             return false;
         }
         let index: usize = index as usize;
-        match self.inventory.foods[index - 1] {
-            Some(_) => true,
-            _ => false,
-        }
+        self.inventory.foods[index - 1].is_some()
     }
 
-    pub fn eat(&mut self, index: i32) {
+    // Returns the food that was eaten
+    pub fn eat(&mut self, index: i32) -> Option<Food> {
         if !self.can_eat(index) {
-            return;
+            return None;
         }
 
         let index: usize = (index - 1) as usize;
-        let food = &self.inventory.foods[index];
-        match food.unwrap() {
-            Food::Apple => self.heal(10),
-            Food::Chicken => self.heal(30),
-            Food::Beef => self.heal(40),
+        let food = self.inventory.foods[index].take()?;
+        self.heal(food.heal_amount());
+        self.satiate(food.saturation());
+        if let Some((effect, turns)) = food.effect() {
+            self.effects.apply(effect, turns);
         }
-        self.inventory.foods[index] = None;
+        Some(food)
     }
 
     pub fn purge_inventory(&mut self) {
@@ -138,14 +214,12 @@ impl Player {
         self.account.pickaxe_level >= 3
     }
 
-    pub fn upgrade_pickaxe_cost(&self) -> u32 {
+    pub fn upgrade_pickaxe_cost(&self) -> Money {
         let pickaxe_level = self.account.pickaxe_level as u32;
-        let base_cost = 100;
-        let cost_per_level = 200;
-
-        let total_cost = pickaxe_level * cost_per_level + base_cost;
+        let base_cost = Money::new(100);
+        let cost_per_level = Money::new(200);
 
-        total_cost
+        cost_per_level * pickaxe_level + base_cost
     }
 
     pub fn upgrade_pickaxe(&mut self) {
@@ -155,15 +229,48 @@ impl Player {
     }
 }
 
-fn mine(iron_chance: u8, gold_chance: u8) -> Ore {
-    let mut rng = rand::thread_rng();
-    let random_num: u8 = rng.gen_range(1..=100);
+fn parse_player_string(player_string: &str) -> Result<(String, Health, Hunger), &'static str> {
+    let tokens: Vec<&str> = player_string.split(',').collect();
+    if tokens.len() != 3 {
+        return Err("Invalid number of tokens");
+    }
+
+    let username = tokens[0].to_string();
+    let health = tokens[1].trim().parse().unwrap_or(MAX_HEALTH);
+    let hunger = tokens[2].trim().parse().unwrap_or(MAX_HUNGER);
+
+    Ok((username, Health::new(health), Hunger::new(hunger)))
+}
+
+// Restores the health and hunger saved for this player, if there are any
+pub fn load_player_state(player: &mut Player, file_path: &str) -> Result<(), io::Error> {
+    let file = File::open(file_path)?;
+    let reader = io::BufReader::new(file);
 
-    if random_num < iron_chance {
-        return Ore::IronOre;
-    } else if random_num < iron_chance + gold_chance {
-        return Ore::GoldOre;
-    } else {
-        return Ore::Diamond;
+    for line_content in reader.lines().map_while(Result::ok) {
+        if let Ok((username, health, hunger)) = parse_player_string(&line_content) {
+            if username == player.account.username {
+                player.health = health;
+                player.hunger = hunger;
+                break;
+            }
+        }
     }
+
+    Ok(())
+}
+
+pub fn update_player_file(players: &[Player], file_path: &str) -> io::Result<()> {
+    let mut file = File::create(file_path)?;
+
+    for player in players {
+        let player_string = format!(
+            "{},{},{}\n",
+            player.account.username, player.health, player.hunger
+        );
+
+        file.write_all(player_string.as_bytes())?;
+    }
+
+    Ok(())
 }
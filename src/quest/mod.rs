@@ -0,0 +1,237 @@
+use crate::event::GameEvent;
+use crate::inventory::{Food, Inventory, Ore};
+use crate::lang::tr;
+use crate::money::Money;
+use crate::player::Player;
+use rand::Rng;
+use std::fs::File;
+use std::io::{self, BufRead, Write};
+
+pub const MAX_ACTIVE_QUESTS: usize = 3;
+
+#[derive(Clone, Copy)]
+pub enum Objective {
+    Deliver {
+        ore: Ore,
+        amount: u32,
+        digs_left: u32,
+    },
+    ReachDepth {
+        depth: u8,
+    },
+}
+
+#[derive(Clone, Copy)]
+pub enum Reward {
+    Money(Money),
+    Food(Food),
+}
+
+#[derive(Clone, Copy)]
+pub struct Quest {
+    pub objective: Objective,
+    pub reward: Reward,
+}
+
+pub enum QuestUpdate {
+    Completed(Quest),
+    Failed(Quest),
+}
+
+pub struct QuestLog {
+    pub active: Vec<Quest>,
+}
+
+impl Quest {
+    pub fn generate() -> Quest {
+        let mut rng = rand::thread_rng();
+        if rng.gen_range(1..=100) <= 60 {
+            let (ore, amount) = match rng.gen_range(1..=100) {
+                1..=50 => (Ore::IronOre, rng.gen_range(4..=8)),
+                51..=85 => (Ore::GoldOre, rng.gen_range(2..=5)),
+                _ => (Ore::Diamond, rng.gen_range(1..=2)),
+            };
+            Quest {
+                objective: Objective::Deliver {
+                    ore,
+                    amount,
+                    digs_left: rng.gen_range(8..=15),
+                },
+                reward: Reward::Money(ore.price() * amount * 2),
+            }
+        } else {
+            let depth = rng.gen_range(10..=20);
+            let reward = if depth >= 15 {
+                Reward::Food(Food::Beef)
+            } else {
+                Reward::Food(Food::Chicken)
+            };
+            Quest {
+                objective: Objective::ReachDepth { depth },
+                reward,
+            }
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        let objective = match self.objective {
+            Objective::Deliver {
+                ore,
+                amount,
+                digs_left,
+            } => tr!("quest.deliver", amount, ore.name(), digs_left),
+            Objective::ReachDepth { depth } => tr!("quest.reach_depth", depth),
+        };
+        let reward = match self.reward {
+            Reward::Money(amount) => format!("${}", amount),
+            Reward::Food(food) => food.name().to_string(),
+        };
+        tr!("quest.with_reward", objective, reward)
+    }
+
+    pub fn can_deliver(&self, inventory: &Inventory) -> bool {
+        match self.objective {
+            Objective::Deliver { ore, amount, .. } => inventory.count_ore(ore) >= amount,
+            Objective::ReachDepth { .. } => false,
+        }
+    }
+}
+
+impl QuestLog {
+    pub fn create_empty() -> Self {
+        QuestLog { active: Vec::new() }
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.active.len() >= MAX_ACTIVE_QUESTS
+    }
+
+    // Advances every active quest, removing the ones that are finished
+    pub fn record(&mut self, event: GameEvent) -> Vec<QuestUpdate> {
+        let mut updates = Vec::new();
+        let mut remaining = Vec::new();
+
+        for mut quest in self.active.drain(..) {
+            match (&mut quest.objective, event) {
+                (Objective::Deliver { digs_left, .. }, GameEvent::Dug) => {
+                    *digs_left = digs_left.saturating_sub(1);
+                    if *digs_left == 0 {
+                        updates.push(QuestUpdate::Failed(quest));
+                        continue;
+                    }
+                }
+                (Objective::ReachDepth { depth }, GameEvent::DepthReached(reached))
+                    if reached >= *depth =>
+                {
+                    updates.push(QuestUpdate::Completed(quest));
+                    continue;
+                }
+                (Objective::ReachDepth { .. }, GameEvent::FoodEaten) => {
+                    updates.push(QuestUpdate::Failed(quest));
+                    continue;
+                }
+                _ => (),
+            }
+            remaining.push(quest);
+        }
+
+        self.active = remaining;
+        updates
+    }
+}
+
+fn parse_quest_token(token: &str) -> Option<Quest> {
+    let fields: Vec<&str> = token.split(',').collect();
+    let (objective, reward_fields) = match fields.first() {
+        Some(&"deliver") if fields.len() == 6 => (
+            Objective::Deliver {
+                ore: Ore::from_string(fields[1])?,
+                amount: fields[2].parse().ok()?,
+                digs_left: fields[3].parse().ok()?,
+            },
+            &fields[4..],
+        ),
+        Some(&"depth") if fields.len() == 4 => (
+            Objective::ReachDepth {
+                depth: fields[1].parse().ok()?,
+            },
+            &fields[2..],
+        ),
+        _ => return None,
+    };
+
+    let reward = match reward_fields {
+        ["money", amount] => Reward::Money(amount.parse().ok()?),
+        ["food", food] => Reward::Food(Food::from_string(food)?),
+        _ => return None,
+    };
+
+    Some(Quest { objective, reward })
+}
+
+fn parse_quest_string(quest_string: &str) -> Result<QuestLog, &'static str> {
+    let mut quest_log = QuestLog::create_empty();
+    // first token is the username
+    for token in quest_string.split(';').skip(1) {
+        if let Some(quest) = parse_quest_token(token) {
+            quest_log.active.push(quest);
+        }
+    }
+
+    Ok(quest_log)
+}
+
+pub fn search_quest_file(file_path: &str, username: &str) -> Result<QuestLog, io::Error> {
+    let file = File::open(file_path)?;
+    let reader = io::BufReader::new(file);
+
+    for line_content in reader.lines().map_while(Result::ok) {
+        if line_content.split(';').next() != Some(username) {
+            continue;
+        }
+
+        if let Ok(quest_log) = parse_quest_string(&line_content) {
+            return Ok(quest_log);
+        }
+    }
+
+    Ok(QuestLog::create_empty())
+}
+
+pub fn update_quest_file(players: &[Player], file_path: &str) -> io::Result<()> {
+    let mut file = File::create(file_path)?;
+
+    for player in players {
+        let mut quest_string = String::new();
+        quest_string.push_str(&player.account.username);
+        quest_string.push(';');
+
+        for quest in &player.quests.active {
+            match quest.objective {
+                Objective::Deliver {
+                    ore,
+                    amount,
+                    digs_left,
+                } => quest_string.push_str(&format!(
+                    "deliver,{},{},{},",
+                    ore.to_string(),
+                    amount,
+                    digs_left
+                )),
+                Objective::ReachDepth { depth } => {
+                    quest_string.push_str(&format!("depth,{},", depth))
+                }
+            }
+            match quest.reward {
+                Reward::Money(amount) => quest_string.push_str(&format!("money,{}", amount)),
+                Reward::Food(food) => quest_string.push_str(&format!("food,{}", food.to_string())),
+            }
+            quest_string.push(';');
+        }
+
+        quest_string.push('\n');
+        file.write_all(quest_string.as_bytes())?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,84 @@
+use crate::account::{parse_account_file, update_account_file, Account};
+use crate::achievement::{search_achievement_file, update_achievement_file};
+use crate::bank::{search_vault_file, update_vault_file};
+use crate::equipment::{search_equipment_file, update_equipment_file};
+use crate::event::GameEvent;
+use crate::farm::{search_farm_file, update_farm_file};
+use crate::inventory::{search_inventory_file, update_inventory_file, Inventory};
+use crate::mine::{search_mine_file, update_mine_file};
+use crate::player::{load_player_state, update_player_file, Player};
+use crate::quest::{search_quest_file, update_quest_file};
+use std::io;
+
+pub const SAVE_DIR: &str = "src";
+
+fn save_path(save_dir: &str, file_name: &str) -> String {
+    format!("{}/{}", save_dir, file_name)
+}
+
+pub fn load_players(save_dir: &str) -> Result<Vec<Player>, io::Error> {
+    let inventory_file_path = save_path(save_dir, "inventory.txt");
+    let achievement_file_path = save_path(save_dir, "achievement.txt");
+    let quest_file_path = save_path(save_dir, "quest.txt");
+    let player_file_path = save_path(save_dir, "player.txt");
+    let equipment_file_path = save_path(save_dir, "equipment.txt");
+    let mine_file_path = save_path(save_dir, "mine.txt");
+    let vault_file_path = save_path(save_dir, "bank.txt");
+    let farm_file_path = save_path(save_dir, "farm.txt");
+
+    let accounts = parse_account_file(&save_path(save_dir, "account.txt"))?;
+    let mut players: Vec<Player> = Vec::new();
+    for account in accounts {
+        let mut player = Player::new(
+            search_inventory_file(&inventory_file_path, &account.username)
+                .unwrap_or(Inventory::create_empty()),
+            account,
+        );
+        let username = player.account.username.clone();
+        if let Ok(achievements) = search_achievement_file(&achievement_file_path, &username) {
+            player.achievements = achievements;
+        }
+        // a missing file just means everyone starts fresh
+        let _ = load_player_state(&mut player, &player_file_path);
+        if let Ok(equipment) = search_equipment_file(&equipment_file_path, &username) {
+            player.equipment = equipment;
+        }
+        if let Ok(mine) = search_mine_file(&mine_file_path, &username) {
+            player.mine = mine;
+        }
+        // a trip that was left unfinished goes on where it was
+        player
+            .expedition
+            .record(GameEvent::DepthReached(player.get_depth()));
+        if let Ok(quests) = search_quest_file(&quest_file_path, &username) {
+            player.quests = quests;
+        }
+        if let Ok(vault) = search_vault_file(&vault_file_path, &username) {
+            player.vault = vault;
+        }
+        if let Ok(farm) = search_farm_file(&farm_file_path, &username) {
+            player.farm = farm;
+        }
+        player
+            .achievements
+            .record(GameEvent::PickaxeUpgraded(player.account.pickaxe_level));
+        players.push(player);
+    }
+
+    Ok(players)
+}
+
+pub fn save_players(players: &[Player], save_dir: &str) -> io::Result<()> {
+    let accounts: Vec<Account> = players.iter().map(|x| x.account.clone()).collect();
+    update_account_file(&accounts, &save_path(save_dir, "account.txt"))?;
+    update_inventory_file(players, &save_path(save_dir, "inventory.txt"))?;
+    update_achievement_file(players, &save_path(save_dir, "achievement.txt"))?;
+    update_quest_file(players, &save_path(save_dir, "quest.txt"))?;
+    update_player_file(players, &save_path(save_dir, "player.txt"))?;
+    update_equipment_file(players, &save_path(save_dir, "equipment.txt"))?;
+    update_mine_file(players, &save_path(save_dir, "mine.txt"))?;
+    update_vault_file(players, &save_path(save_dir, "bank.txt"))?;
+    update_farm_file(players, &save_path(save_dir, "farm.txt"))?;
+
+    Ok(())
+}
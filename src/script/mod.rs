@@ -0,0 +1,767 @@
+// Non-interactive mode, reads one command per line and answers with
+// tab separated lines so other programs can read the results:
+//   log     <message>           something that happened in the game
+//   ok      <command>           the command finished
+//   error   <command>  <reason> the command could not be run
+//   status  key=value...        answer to the `status` command
+// Commands, item names and error reasons stay in English whatever the
+// --lang option says, only the log messages get translated.
+use crate::auction::{self, DEFAULT_DURATION};
+use crate::bank::{self, VAULT_SLOTS};
+use crate::clock::Clock;
+use crate::consumable::{Consumable, ALL_CONSUMABLES};
+use crate::equipment::{Gear, ALL_GEAR};
+use crate::farm::{self, Crop};
+use crate::game;
+use crate::inventory::{Food, Ore, SHOP_FOODS};
+use crate::lang::tr;
+use crate::mine::Direction;
+use crate::money::Money;
+use crate::player::Player;
+use crate::trade::{self, Goods};
+use crate::world::{save_world, World};
+use std::io::{self, BufRead};
+
+pub const HELP: &str = "login <name>, register <name>, logout, dig [count], \
+move <north|south|east|west|down> [count], return, wait, eat [index], cook [index], sell all, \
+buy <food|gear|supply|pickaxe>, craft <gear>, equip <gear>, use <supply>, status, \
+offer <name> <goods> [for <goods>], trades, accept <id>, decline <id>, \
+bank, deposit <goods>, withdraw <goods>, statement, auctions, \
+list <count> <item> for <price> [turns], bid <id> <amount>, unlist <id>, \
+farm, plant <crop>, harvest, save, quit";
+
+// Most digs or moves one command can repeat, so a single line can't keep
+// the server's world locked for long
+pub const MAX_COUNT: u32 = 100;
+
+#[derive(Default)]
+pub struct Session {
+    player: Option<usize>,
+    quit: bool,
+}
+
+impl Session {
+    // Set once the `quit` command was given
+    pub fn has_quit(&self) -> bool {
+        self.quit
+    }
+
+    pub fn username<'a>(&self, world: &'a World) -> Option<&'a str> {
+        let player = world.players.get(self.player?)?;
+        Some(&player.account.username)
+    }
+}
+
+pub fn run(world: &mut World, input: impl BufRead) -> io::Result<()> {
+    let mut session = Session::default();
+
+    for line in input.lines() {
+        for reply in answer(&mut session, world, &line?) {
+            println!("{}", reply);
+        }
+        if session.has_quit() {
+            break;
+        }
+    }
+
+    save_world(world)
+}
+
+// Runs one line of input, returning the lines to send back. Blank lines and
+// `#` comments get no answer at all.
+pub fn answer(session: &mut Session, world: &mut World, line: &str) -> Vec<String> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return Vec::new();
+    }
+
+    let command = line.split_whitespace().next().unwrap_or_default();
+    let mut log = Vec::new();
+    let mut output = Vec::new();
+    let result = execute(session, world, line, &mut log, &mut output);
+
+    let mut replies: Vec<String> = log
+        .into_iter()
+        .map(|message| format!("log\t{}", message))
+        .collect();
+    replies.append(&mut output);
+    replies.push(match result {
+        Ok(()) => format!("ok\t{}", command),
+        Err(reason) => format!("error\t{}\t{}", command, reason),
+    });
+    replies
+}
+
+fn execute(
+    session: &mut Session,
+    world: &mut World,
+    line: &str,
+    log: &mut Vec<String>,
+    output: &mut Vec<String>,
+) -> Result<(), String> {
+    let tokens: Vec<&str> = line.split_whitespace().collect();
+    let argument = tokens[1..].join(" ").to_lowercase();
+
+    match tokens[0].to_lowercase().as_str() {
+        "login" => {
+            let players = &mut world.players;
+            let index = players
+                .iter()
+                .position(|player| player.account.username == tokens[1..].join(" "))
+                .ok_or("no such account")?;
+            session.player = Some(index);
+            log.push(tr!("main.welcome_player", players[index].account.username));
+            return Ok(());
+        }
+        "register" => {
+            let username = tokens.get(1).ok_or("missing username")?;
+            world
+                .add_player(username.to_string())
+                .map_err(|e| e.reason())?;
+            session.player = Some(world.players.len() - 1);
+            log.push(tr!("register.done", username));
+            return Ok(());
+        }
+        "save" => return save_world(world).map_err(|e| e.to_string()),
+        "quit" | "exit" => {
+            session.quit = true;
+            return Ok(());
+        }
+        "help" => {
+            log.push(HELP.to_string());
+            return Ok(());
+        }
+        _ => (),
+    }
+
+    let command = tokens[0].to_lowercase();
+    let player = session.player.and_then(|index| world.players.get(index));
+    // the town is only reached by climbing out of the mine
+    let in_town = matches!(
+        command.as_str(),
+        "wait"
+            | "cook"
+            | "sell"
+            | "buy"
+            | "craft"
+            | "offer"
+            | "accept"
+            | "bank"
+            | "deposit"
+            | "withdraw"
+            | "statement"
+            | "auctions"
+            | "list"
+            | "bid"
+            | "unlist"
+            | "farm"
+            | "plant"
+            | "harvest"
+    );
+    if in_town && player.is_some_and(Player::is_in_mine) {
+        return Err("still in the mine, `return` to the surface first".to_string());
+    }
+
+    if let Some(result) = trade_command(session, world, &tokens, log, output) {
+        return result;
+    }
+    if let Some(result) = auction_command(session, world, &tokens, log, output) {
+        return result;
+    }
+
+    let player = session
+        .player
+        .and_then(|index| world.players.get_mut(index))
+        .ok_or("not logged in")?;
+    let clock = &mut world.info.clock;
+    // each dig that got made is a turn for the auction house
+    let mut turns = 0;
+
+    match tokens[0].to_lowercase().as_str() {
+        "logout" => session.player = None,
+        "status" => output.push(status_line(player, clock)),
+        "dig" => {
+            let count = parse_count(tokens.get(1))?;
+            // no point trying again once a dig was refused
+            for _ in 0..count {
+                if !game::dig(player, clock, Direction::Down, log) {
+                    break;
+                }
+                turns += 1;
+            }
+            if turns == 0 && count > 0 {
+                require(false, log)?;
+            }
+        }
+        "move" => {
+            let direction = match tokens.get(1).map(|x| x.to_lowercase()).as_deref() {
+                Some("north") | Some("n") => Direction::North,
+                Some("south") | Some("s") => Direction::South,
+                Some("east") | Some("e") => Direction::East,
+                Some("west") | Some("w") => Direction::West,
+                Some("down") | Some("d") => Direction::Down,
+                _ => return Err("unknown direction".to_string()),
+            };
+            let count = parse_count(tokens.get(2))?;
+            for _ in 0..count {
+                if !game::dig(player, clock, direction, log) {
+                    break;
+                }
+                turns += 1;
+            }
+            if turns == 0 && count > 0 {
+                require(false, log)?;
+            }
+        }
+        "return" => {
+            if !player.is_in_mine() {
+                return Err("not in the mine".to_string());
+            }
+            game::leave_mine(player, clock, log);
+        }
+        "wait" => require(game::wait_for_shop(player, clock, log), log)?,
+        "cook" => {
+            let index = match tokens.get(1) {
+                Some(index) => index.parse().map_err(|_| "invalid index")?,
+                None => {
+                    let foods = &player.inventory.foods;
+                    let raw = foods
+                        .iter()
+                        .position(|x| x.is_some_and(|x| x.cooked().is_some()));
+                    raw.ok_or("nothing to cook")? as i32 + 1
+                }
+            };
+            require(game::cook(player, clock, index, log), log)?;
+        }
+        "eat" => {
+            let index = match tokens.get(1) {
+                Some(index) => index.parse().map_err(|_| "invalid index")?,
+                None => {
+                    let first = player.inventory.foods.iter().position(|x| x.is_some());
+                    first.ok_or("no food")? as i32 + 1
+                }
+            };
+            require(game::eat(player, index, log), log)?;
+        }
+        "sell" => {
+            if argument != "all" {
+                return Err("only `sell all` is supported".to_string());
+            }
+            require(game::shop_is_open(clock, log), log)?;
+            game::sell_all_ores(player, &mut world.market, log);
+        }
+        "buy" => {
+            require(game::shop_is_open(clock, log), log)?;
+            let bought = if argument == "pickaxe" {
+                game::upgrade_pickaxe(player, log)
+            } else if let Some(food) = find_food(&argument) {
+                game::buy_food(player, food, log)
+            } else if let Some(gear) = find_gear(&argument) {
+                game::buy_gear(player, gear, log)
+            } else if let Some(consumable) = find_consumable(&argument) {
+                game::buy_consumable(player, consumable, log)
+            } else {
+                return Err("unknown item".to_string());
+            };
+            require(bought, log)?;
+        }
+        "craft" => {
+            let gear = find_gear(&argument).ok_or("unknown gear")?;
+            require(game::craft_gear(player, clock, gear, log), log)?;
+        }
+        "use" => {
+            let consumable = find_consumable(&argument).ok_or("unknown item")?;
+            require(game::use_consumable(player, clock, consumable, log), log)?;
+        }
+        "equip" => {
+            let gear = find_gear(&argument).ok_or("unknown gear")?;
+            let slot = player
+                .inventory
+                .gear
+                .iter_mut()
+                .find(|x| **x == Some(gear))
+                .ok_or("not in your gear bag")?;
+            *slot = player.equipment.equip(gear);
+            log.push(tr!("equipment.equipped_gear", gear.name()));
+        }
+        "bank" => {
+            bank::pay_interest(player, log);
+            let vault = &player.vault;
+            output.push(format!(
+                "bank\tgoods={}\tslots={}/{}",
+                vault.goods.to_save_string(),
+                vault.used_slots(),
+                VAULT_SLOTS
+            ));
+        }
+        "deposit" => {
+            bank::pay_interest(player, log);
+            let goods = Goods::parse(&argument)?;
+            require(bank::deposit(player, goods, log), log)?;
+        }
+        "withdraw" => {
+            bank::pay_interest(player, log);
+            let goods = Goods::parse(&argument)?;
+            require(bank::withdraw(player, goods, log), log)?;
+        }
+        "statement" => {
+            bank::pay_interest(player, log);
+            for entry in &player.vault.statement {
+                output.push(format!(
+                    "entry\ttime={}\tkind={}\tgoods={}",
+                    entry.time,
+                    entry.kind.to_string(),
+                    entry.goods.to_save_string()
+                ));
+            }
+        }
+        "farm" => {
+            for (i, plot) in player.farm.plots.iter().enumerate() {
+                let (crop, growth) = match plot {
+                    Some(planting) => (planting.crop.to_string(), planting.growth(clock)),
+                    None => ("", 0),
+                };
+                output.push(format!(
+                    "plot\tslot={}\tcrop={}\tgrowth={}",
+                    i + 1,
+                    crop,
+                    growth
+                ));
+            }
+        }
+        "plant" => {
+            let crop = Crop::from_string(&argument).ok_or("unknown crop")?;
+            require(farm::plant(player, clock, crop, log), log)?;
+        }
+        "harvest" => require(farm::harvest(player, clock, log), log)?,
+        _ => return Err("unknown command".to_string()),
+    }
+
+    if turns > 0 {
+        let username = player.account.username.clone();
+        for _ in 0..turns {
+            auction::advance(&mut world.players, &mut world.auctions, &username, log);
+        }
+    }
+    Ok(())
+}
+
+// Goods are written like `100 money, 2 diamond`, trades are listed with
+// their goods the way they are saved
+fn trade_command(
+    session: &Session,
+    world: &mut World,
+    tokens: &[&str],
+    log: &mut Vec<String>,
+    output: &mut Vec<String>,
+) -> Option<Result<(), String>> {
+    let command = tokens[0].to_lowercase();
+    if !matches!(command.as_str(), "offer" | "trades" | "accept" | "decline") {
+        return None;
+    }
+    let Some(username) = session.username(world).map(str::to_string) else {
+        return Some(Err("not logged in".to_string()));
+    };
+
+    let parse_id = || -> Result<u32, String> {
+        let id = tokens.get(1).ok_or("missing trade id")?;
+        id.parse().map_err(|_| "invalid trade id".to_string())
+    };
+    let result = match command.as_str() {
+        "offer" => (|| {
+            let to = tokens.get(1).ok_or("missing username")?;
+            let rest = &tokens[2..];
+            let (give, want) = match rest.iter().position(|x| x.eq_ignore_ascii_case("for")) {
+                Some(split) => (&rest[..split], &rest[split + 1..]),
+                None => (rest, &[][..]),
+            };
+            let (give, want) = (
+                Goods::parse(&give.join(" "))?,
+                Goods::parse(&want.join(" "))?,
+            );
+            let World {
+                players, trades, ..
+            } = world;
+            require(
+                trade::propose(players, trades, &username, to, give, want, log),
+                log,
+            )
+        })(),
+        "trades" => {
+            for trade in world
+                .trades
+                .iter()
+                .filter(|trade| trade.from == username || trade.to == username)
+            {
+                output.push(format!(
+                    "trade\tid={}\tfrom={}\tto={}\tgive={}\twant={}",
+                    trade.id,
+                    trade.from,
+                    trade.to,
+                    trade.give.to_save_string(),
+                    trade.want.to_save_string()
+                ));
+            }
+            Ok(())
+        }
+        "accept" => parse_id().and_then(|id| {
+            let World {
+                players, trades, ..
+            } = world;
+            require(trade::accept(players, trades, &username, id, log), log)
+        }),
+        _ => parse_id()
+            .and_then(|id| require(trade::cancel(&mut world.trades, &username, id, log), log)),
+    };
+    Some(result)
+}
+
+// Auctions are listed with `ends_in` counting the turns left
+fn auction_command(
+    session: &Session,
+    world: &mut World,
+    tokens: &[&str],
+    log: &mut Vec<String>,
+    output: &mut Vec<String>,
+) -> Option<Result<(), String>> {
+    let command = tokens[0].to_lowercase();
+    if !matches!(command.as_str(), "auctions" | "list" | "bid" | "unlist") {
+        return None;
+    }
+    let (Some(index), Some(username)) = (session.player, session.username(world)) else {
+        return Some(Err("not logged in".to_string()));
+    };
+    let username = username.to_string();
+
+    let parse_id = || -> Result<u32, String> {
+        let id = tokens.get(1).ok_or("missing auction id")?;
+        id.parse().map_err(|_| "invalid auction id".to_string())
+    };
+    let World {
+        players, auctions, ..
+    } = world;
+    let result = match command.as_str() {
+        "auctions" => {
+            for listing in &auctions.listings {
+                let (bidder, bid) = match &listing.bid {
+                    Some(bid) => (bid.bidder.as_str(), bid.amount),
+                    None => ("", Money::ZERO),
+                };
+                output.push(format!(
+                    "listing\tid={}\tseller={}\titem={}\tcount={}\tstart={}\tbid={}\tbidder={}\tends_in={}",
+                    listing.id,
+                    listing.seller,
+                    listing.item.to_string(),
+                    listing.count,
+                    listing.start_price,
+                    bid,
+                    bidder,
+                    listing.turns_left(auctions)
+                ));
+            }
+            Ok(())
+        }
+        "list" => (|| {
+            let rest = &tokens[1..];
+            let split = rest
+                .iter()
+                .position(|x| x.eq_ignore_ascii_case("for"))
+                .ok_or("missing price")?;
+            let lot = Goods::parse(&rest[..split].join(" "))?;
+            let [(item, count)] = lot.items[..] else {
+                return Err("list one kind of item".to_string());
+            };
+            if !lot.money.is_zero() {
+                return Err("money can't be auctioned".to_string());
+            }
+            let price = rest.get(split + 1).ok_or("missing price")?;
+            let price = price.parse().map_err(|_| "invalid price")?;
+            let duration = match rest.get(split + 2) {
+                Some(turns) => turns.parse().map_err(|_| "invalid turns")?,
+                None => DEFAULT_DURATION,
+            };
+            let player = &mut players[index];
+            require(
+                auction::list(player, auctions, item, count, price, duration, log),
+                log,
+            )
+        })(),
+        "bid" => parse_id().and_then(|id| {
+            let amount = tokens.get(2).ok_or("missing amount")?;
+            let amount = amount.parse().map_err(|_| "invalid amount")?;
+            require(
+                auction::bid(players, auctions, &username, id, amount, log),
+                log,
+            )
+        }),
+        _ => parse_id().and_then(|id| {
+            require(
+                auction::withdraw(players, auctions, &username, id, log),
+                log,
+            )
+        }),
+    };
+    Some(result)
+}
+
+fn parse_count(token: Option<&&str>) -> Result<u32, String> {
+    let count = match token {
+        Some(count) => count.parse().map_err(|_| "invalid count")?,
+        None => 1,
+    };
+    if count > MAX_COUNT {
+        return Err(format!("count must be at most {}", MAX_COUNT));
+    }
+    Ok(count)
+}
+
+// Turns a failed game action into an error, using its last log message
+fn require(done: bool, log: &mut Vec<String>) -> Result<(), String> {
+    if done {
+        Ok(())
+    } else {
+        Err(log.pop().unwrap_or_default())
+    }
+}
+
+fn find_food(name: &str) -> Option<Food> {
+    SHOP_FOODS
+        .iter()
+        .copied()
+        .find(|food| food.to_string().to_lowercase() == name)
+}
+
+fn find_gear(name: &str) -> Option<Gear> {
+    ALL_GEAR
+        .iter()
+        .copied()
+        .find(|gear| gear.to_string().to_lowercase() == name)
+}
+
+fn find_consumable(name: &str) -> Option<Consumable> {
+    ALL_CONSUMABLES
+        .iter()
+        .copied()
+        .find(|consumable| consumable.to_string().to_lowercase() == name)
+}
+
+fn status_line(player: &Player, clock: &Clock) -> String {
+    let (x, y, depth) = player.mine.position();
+    format!(
+        "status\tusername={}\tmoney={}\thealth={}\thunger={}\tx={}\ty={}\tdepth={}\tpickaxe={}\tiron={}\tgold={}\tdiamond={}\tcoal={}\tday={}\ttime={}\tnight={}",
+        player.account.username,
+        player.account.money,
+        player.get_health(),
+        player.get_hunger(),
+        x,
+        y,
+        depth,
+        player.account.pickaxe_level,
+        player.inventory.count_ore(Ore::IronOre),
+        player.inventory.count_ore(Ore::GoldOre),
+        player.inventory.count_ore(Ore::Diamond),
+        player.inventory.count_ore(Ore::Coal),
+        clock.day(),
+        clock.time(),
+        clock.is_night()
+    )
+}
+
+// Random command sequences run against worlds saved with values at the very
+// edge of what their types hold, none of them may panic or wrap around
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auction::Listing;
+    use crate::clock::MINUTES_PER_DAY;
+    use crate::farm::Planting;
+    use crate::inventory::Item;
+    use crate::player::{MAX_HEALTH, MAX_HUNGER};
+    use crate::trade::Trade;
+    use crate::world::{create_world, open_world};
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+    use rand::SeedableRng;
+    use std::fs;
+
+    const RUNS: u64 = 40;
+    const COMMANDS_PER_RUN: usize = 300;
+
+    // `#` is replaced by a number, `@` by an account name
+    const TEMPLATES: [&str; 48] = [
+        "dig #",
+        "dig",
+        "move north #",
+        "move south #",
+        "move east #",
+        "move west #",
+        "move down #",
+        "return",
+        "wait",
+        "eat",
+        "eat #",
+        "cook",
+        "cook #",
+        "sell all",
+        "buy apple",
+        "buy beef",
+        "buy pickaxe",
+        "buy iron helmet",
+        "buy healing potion",
+        "buy tnt",
+        "buy torch",
+        "buy rope",
+        "craft iron sword",
+        "equip iron helmet",
+        "use healing potion",
+        "use tnt",
+        "use torch",
+        "use rope",
+        "bank",
+        "deposit # money",
+        "deposit # iron ore, # apple",
+        "withdraw # money",
+        "withdraw # apple",
+        "statement",
+        "offer @ # money for # coal",
+        "offer @ # iron ore",
+        "accept #",
+        "decline #",
+        "trades",
+        "list # coal for # #",
+        "list # apple for #",
+        "bid # #",
+        "unlist #",
+        "auctions",
+        "plant wheat",
+        "harvest",
+        "login @",
+        "status",
+    ];
+
+    const NUMBERS: [&str; 12] = [
+        "0",
+        "1",
+        "2",
+        "7",
+        "100",
+        "101",
+        "255",
+        "256",
+        "4294967294",
+        "4294967295",
+        "4294967296",
+        "18446744073709551615",
+    ];
+
+    const NAMES: [&str; 3] = ["alice", "bob", "nobody"];
+
+    // Commands after which someone may have less money than before
+    fn spends(command: &str) -> bool {
+        matches!(
+            command,
+            "buy" | "craft" | "deposit" | "offer" | "accept" | "bid" | "plant"
+        )
+    }
+
+    fn random_command(rng: &mut StdRng) -> String {
+        let template = TEMPLATES.choose(rng).unwrap();
+        let mut command = String::new();
+        for c in template.chars() {
+            match c {
+                '#' => command.push_str(NUMBERS.choose(rng).unwrap()),
+                '@' => command.push_str(NAMES.choose(rng).unwrap()),
+                c => command.push(c),
+            }
+        }
+        command
+    }
+
+    // A world in the temp dir where everything is about to overflow
+    fn edge_world(root: &str) -> World {
+        create_world(root, "edge").unwrap();
+        let mut world = open_world(root, "edge").unwrap();
+        for name in ["alice", "bob"] {
+            let mut session = Session::default();
+            answer(&mut session, &mut world, &format!("register {}", name));
+        }
+
+        world.info.clock.minutes = u64::MAX - 2 * MINUTES_PER_DAY;
+        world.players[0].account.money = Money::MAX;
+        world.players[0].vault.goods.money = Money::new(u32::MAX - 10);
+        world.players[0].farm.plots[0] = Some(Planting {
+            crop: Crop::Apple,
+            planted: 0,
+        });
+        world.trades.push(Trade {
+            id: u32::MAX,
+            from: "alice".to_string(),
+            to: "bob".to_string(),
+            give: Goods::default(),
+            want: Goods::parse("1 money").unwrap(),
+        });
+        world.auctions.turn = u64::MAX - 5;
+        world.auctions.listings.push(Listing {
+            id: u32::MAX,
+            seller: "bob".to_string(),
+            item: Item::Ore(Ore::Coal),
+            count: 1,
+            start_price: Money::new(u32::MAX - 1),
+            ends: u64::MAX,
+            bid: None,
+        });
+
+        // going through the save files checks they read back the edges too
+        save_world(&mut world).unwrap();
+        open_world(root, "edge").unwrap()
+    }
+
+    fn money_of(world: &World) -> Vec<Money> {
+        world.players.iter().map(|x| x.account.money).collect()
+    }
+
+    #[test]
+    fn random_commands_never_panic_or_wrap() {
+        let root = std::env::temp_dir().join(format!("text_craft_script_{}", std::process::id()));
+        let root = root.to_str().unwrap().to_string();
+
+        for seed in 0..RUNS {
+            let _ = fs::remove_dir_all(&root);
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut world = edge_world(&root);
+            let mut session = Session::default();
+            answer(&mut session, &mut world, "login alice");
+
+            for _ in 0..COMMANDS_PER_RUN {
+                let line = random_command(&mut rng);
+                let command = line.split_whitespace().next().unwrap().to_string();
+                let money = money_of(&world);
+                let minutes = world.info.clock.minutes;
+                let turn = world.auctions.turn;
+
+                answer(&mut session, &mut world, &line);
+
+                assert!(
+                    world.info.clock.minutes >= minutes,
+                    "clock wrapped: {}",
+                    line
+                );
+                assert!(world.auctions.turn >= turn, "turn wrapped: {}", line);
+                for (player, before) in world.players.iter().zip(money) {
+                    assert!(player.get_health() <= MAX_HEALTH, "health: {}", line);
+                    assert!(player.get_hunger() <= MAX_HUNGER, "hunger: {}", line);
+                    assert!(
+                        player.account.money >= before || spends(&command),
+                        "{} lost money on `{}` (seed {})",
+                        player.account.username,
+                        line,
+                        seed
+                    );
+                }
+            }
+            save_world(&mut world).unwrap();
+        }
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,157 @@
+// Hosts one world over TCP for several players at once. Every connection
+// speaks the same line protocol as the script mode and plays its own
+// account. The world, its market and the saves live here behind a single
+// lock, so commands from different players never interleave.
+use crate::lang::{text, tr};
+use crate::script::{self, Session};
+use crate::world::{save_world, World};
+use std::collections::HashSet;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+
+pub const DEFAULT_ADDRESS: &str = "127.0.0.1:7878";
+// Commands run by all players together between two saves
+const SAVE_EVERY: u32 = 50;
+
+struct Shared {
+    world: World,
+    // accounts somebody is playing with right now
+    online: HashSet<String>,
+    commands_since_save: u32,
+}
+
+impl Shared {
+    fn save(&mut self) {
+        self.commands_since_save = 0;
+        if let Err(e) = save_world(&mut self.world) {
+            eprintln!("{}", tr!("main.save_failed", e));
+        }
+    }
+}
+
+// A client that panicked while holding the lock can't have left the world
+// half written, every command runs to the end before the lock is released
+fn lock(shared: &Mutex<Shared>) -> MutexGuard<'_, Shared> {
+    shared.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+pub fn run(world: World, address: &str) -> io::Result<()> {
+    let listener = TcpListener::bind(address)?;
+    println!(
+        "{}",
+        tr!("server.listening", world.info.name, listener.local_addr()?)
+    );
+
+    let shared = Arc::new(Mutex::new(Shared {
+        world,
+        online: HashSet::new(),
+        commands_since_save: 0,
+    }));
+
+    let on_interrupt = Arc::clone(&shared);
+    let result = ctrlc::set_handler(move || {
+        lock(&on_interrupt).save();
+        println!("{}", text("main.saved_goodbye"));
+        std::process::exit(0);
+    });
+    if result.is_err() {
+        println!("{}", text("input.no_interrupt_handler"));
+    }
+
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                eprintln!("{}", tr!("server.accept_failed", e));
+                continue;
+            }
+        };
+        let shared = Arc::clone(&shared);
+        thread::spawn(move || {
+            let peer = stream
+                .peer_addr()
+                .map(|x| x.to_string())
+                .unwrap_or_default();
+            println!("{}", tr!("server.connected", peer));
+            if let Err(e) = serve(stream, &shared) {
+                eprintln!("{}", tr!("server.connection_failed", peer, e));
+            }
+            println!("{}", tr!("server.disconnected", peer));
+        });
+    }
+    Ok(())
+}
+
+fn serve(stream: TcpStream, shared: &Mutex<Shared>) -> io::Result<()> {
+    let reader = BufReader::new(stream.try_clone()?);
+    let mut writer = stream;
+    let mut session = Session::default();
+
+    let world_name = lock(shared).world.info.name.clone();
+    writeln!(writer, "log\t{}", tr!("server.welcome", world_name))?;
+
+    let mut result = Ok(());
+    for line in reader.lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => {
+                result = Err(e);
+                break;
+            }
+        };
+
+        let replies = answer(&mut session, shared, &line);
+        if let Err(e) = replies
+            .iter()
+            .try_for_each(|reply| writeln!(writer, "{}", reply))
+        {
+            result = Err(e);
+            break;
+        }
+        if session.has_quit() {
+            break;
+        }
+    }
+
+    // the account is free again and nothing the player did gets lost
+    let mut shared = lock(shared);
+    if let Some(username) = session.username(&shared.world).map(str::to_string) {
+        shared.online.remove(&username);
+    }
+    shared.save();
+    result
+}
+
+fn answer(session: &mut Session, shared: &Mutex<Shared>, line: &str) -> Vec<String> {
+    let mut shared = lock(shared);
+    let before = session.username(&shared.world).map(str::to_string);
+
+    // an account can only be played from one connection at a time
+    let tokens: Vec<&str> = line.split_whitespace().collect();
+    if tokens.first().map(|x| x.to_lowercase()).as_deref() == Some("login") {
+        let username = tokens[1..].join(" ");
+        if shared.online.contains(&username) && before.as_deref() != Some(username.as_str()) {
+            return vec!["error\tlogin\talready playing on another connection".to_string()];
+        }
+    }
+
+    let replies = script::answer(session, &mut shared.world, line);
+
+    let after = session.username(&shared.world).map(str::to_string);
+    if before != after {
+        if let Some(username) = before {
+            shared.online.remove(&username);
+        }
+        if let Some(username) = after {
+            shared.online.insert(username);
+        }
+    }
+
+    shared.commands_since_save += 1;
+    if shared.commands_since_save >= SAVE_EVERY {
+        shared.save();
+    }
+    replies
+}
@@ -0,0 +1,57 @@
+// A stat that stays between zero and its maximum, like health or hunger.
+// Raising or lowering it stops at either end instead of wrapping around.
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Stat<const MAX: u8>(u8);
+
+impl<const MAX: u8> Stat<MAX> {
+    // Values above the maximum are cut down to it
+    pub fn new(value: u8) -> Self {
+        Stat(value.min(MAX))
+    }
+
+    pub fn full() -> Self {
+        Stat(MAX)
+    }
+
+    pub fn get(self) -> u8 {
+        self.0
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn raise(&mut self, amount: u8) {
+        self.0 = self.0.saturating_add(amount).min(MAX);
+    }
+
+    pub fn lower(&mut self, amount: u8) {
+        self.0 = self.0.saturating_sub(amount);
+    }
+}
+
+impl<const MAX: u8> fmt::Display for Stat<MAX> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stays_between_zero_and_its_maximum() {
+        assert_eq!(Stat::<10>::new(20).get(), 10);
+        assert_eq!(Stat::<10>::full().get(), 10);
+
+        let mut stat = Stat::<10>::new(8);
+        stat.raise(u8::MAX);
+        assert_eq!(stat.get(), 10);
+        stat.lower(u8::MAX);
+        assert_eq!(stat.get(), 0);
+        assert!(stat.is_empty());
+    }
+}
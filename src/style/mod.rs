@@ -0,0 +1,136 @@
+// Terminal colors for the line based menus. Everything falls back to plain
+// text when NO_COLOR is set or the output isn't a terminal, so piped output
+// and the script mode never see escape codes.
+use crate::inventory::{Ore, Rarity, ALL_ORES};
+use crate::lang::text;
+use std::io::IsTerminal;
+use std::sync::OnceLock;
+
+#[derive(Clone, Copy)]
+pub enum Color {
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Cyan,
+    Gray,
+}
+
+// Width of the health and hunger bars, in characters
+const METER_WIDTH: u32 = 20;
+
+static ENABLED: OnceLock<bool> = OnceLock::new();
+
+impl Color {
+    fn code(self) -> &'static str {
+        match self {
+            Color::Red => "31",
+            Color::Green => "32",
+            Color::Yellow => "33",
+            Color::Blue => "34",
+            Color::Cyan => "36",
+            Color::Gray => "90",
+        }
+    }
+}
+
+impl Rarity {
+    fn color(self) -> Option<Color> {
+        match self {
+            Rarity::Common => None,
+            Rarity::Uncommon => Some(Color::Yellow),
+            Rarity::Rare => Some(Color::Cyan),
+        }
+    }
+}
+
+pub fn enabled() -> bool {
+    *ENABLED.get_or_init(|| {
+        // https://no-color.org, any non empty value turns colors off
+        let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+        !no_color && std::io::stdout().is_terminal()
+    })
+}
+
+pub fn paint(text: &str, color: Color) -> String {
+    if !enabled() {
+        return text.to_string();
+    }
+    format!("\x1b[{}m{}\x1b[0m", color.code(), text)
+}
+
+pub fn bold(text: &str) -> String {
+    if !enabled() {
+        return text.to_string();
+    }
+    format!("\x1b[1m{}\x1b[0m", text)
+}
+
+pub fn error(text: &str) -> String {
+    if !enabled() {
+        return text.to_string();
+    }
+    format!("\x1b[1;31m{}\x1b[0m", text)
+}
+
+pub fn dim(text: &str) -> String {
+    paint(text, Color::Gray)
+}
+
+pub fn item(name: &str, rarity: Rarity) -> String {
+    match rarity.color() {
+        Some(color) => bold(&paint(name, color)),
+        None => name.to_string(),
+    }
+}
+
+// Colors every ore named in a game message, so rare finds stand out
+pub fn highlight(message: &str) -> String {
+    let mut result = message.to_string();
+    for ore in ALL_ORES {
+        if ore.rarity() != Rarity::Common {
+            result = result.replace(ore.name(), &item(ore.name(), ore.rarity()));
+        }
+    }
+    result
+}
+
+// [##########----------] colored green, yellow or red by how full it is
+pub fn meter(value: u8, max: u8) -> String {
+    let max = max.max(1) as u32;
+    let value = (value as u32).min(max);
+    let filled = (value * METER_WIDTH / max) as usize;
+    let bar = format!(
+        "[{}{}]",
+        "#".repeat(filled),
+        "-".repeat(METER_WIDTH as usize - filled)
+    );
+
+    let color = match value * 100 / max {
+        60.. => Color::Green,
+        30..=59 => Color::Yellow,
+        _ => Color::Red,
+    };
+    paint(&bar, color)
+}
+
+pub fn map_row(row: &str) -> String {
+    if !enabled() {
+        return row.to_string();
+    }
+    row.chars()
+        .map(|glyph| match glyph {
+            '@' => bold(&glyph.to_string()),
+            'X' => dim(&glyph.to_string()),
+            'g' => item(&glyph.to_string(), Ore::GoldOre.rarity()),
+            'd' => item(&glyph.to_string(), Ore::Diamond.rarity()),
+            '^' => paint(&glyph.to_string(), Color::Red),
+            '~' => paint(&glyph.to_string(), Color::Blue),
+            _ => glyph.to_string(),
+        })
+        .collect()
+}
+
+pub fn empty() -> String {
+    dim(text("item.empty"))
+}
@@ -0,0 +1,44 @@
+// Real-world time, as opposed to the in-game clock: when achievements were
+// unlocked, worlds created and vault entries made.
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Seconds since the unix epoch
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+// `YYYY-MM-DD HH:MM` in UTC, or "-" for a timestamp that was never set
+pub fn format_time(timestamp: u64) -> String {
+    if timestamp == 0 {
+        return "-".to_string();
+    }
+
+    // days since 1970-01-01 to a civil date, from Howard Hinnant's algorithm
+    let days = (timestamp / 86400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    let seconds_of_day = timestamp % 86400;
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        year,
+        month,
+        day,
+        seconds_of_day / 3600,
+        seconds_of_day % 3600 / 60
+    )
+}
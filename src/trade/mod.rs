@@ -0,0 +1,455 @@
+// Trades between two accounts. The one offering puts up some goods and asks
+// for goods in return. Nothing is held back while the offer waits, so when
+// it gets accepted both sides are checked again and everything moves at
+// once, or nothing does.
+use crate::inventory::Item;
+use crate::lang::{text, tr};
+use crate::money::Money;
+use crate::player::Player;
+use std::fs::File;
+use std::io::{self, BufRead, Write};
+
+#[derive(Clone, Default, PartialEq)]
+pub struct Goods {
+    pub money: Money,
+    pub items: Vec<(Item, u32)>,
+}
+
+pub struct Trade {
+    pub id: u32,
+    pub from: String,
+    pub to: String,
+    pub give: Goods,
+    pub want: Goods,
+}
+
+impl Goods {
+    pub fn is_empty(&self) -> bool {
+        self.money.is_zero() && self.items.is_empty()
+    }
+
+    // Nothing is added for an amount of 0, so goods never list empty items
+    pub fn add_item(&mut self, item: Item, amount: u32) {
+        if amount == 0 {
+            return;
+        }
+        match self.items.iter_mut().find(|(x, _)| *x == item) {
+            Some((_, count)) => *count = count.saturating_add(amount),
+            None => self.items.push((item, amount)),
+        }
+    }
+
+    pub fn count(&self, item: Item) -> u32 {
+        self.pieces(|x| x == item)
+    }
+
+    // How many items there are in total among the ones `wanted` picks
+    pub fn pieces(&self, wanted: impl Fn(Item) -> bool) -> u32 {
+        self.items
+            .iter()
+            .filter(|(x, _)| wanted(*x))
+            .fold(0, |total, (_, count)| total.saturating_add(*count))
+    }
+
+    // What the player sees, like `$100, 2 Diamond`
+    pub fn describe(&self) -> String {
+        if self.is_empty() {
+            return text("trade.nothing").to_string();
+        }
+        let mut parts = Vec::new();
+        if !self.money.is_zero() {
+            parts.push(format!("${}", self.money));
+        }
+        for (item, count) in &self.items {
+            parts.push(format!("{} {}", count, item.name()));
+        }
+        parts.join(", ")
+    }
+
+    // `money:100,Diamond:2`, how goods get saved
+    pub fn to_save_string(&self) -> String {
+        let mut parts = vec![format!("money:{}", self.money)];
+        for (item, count) in &self.items {
+            parts.push(format!("{}:{}", item.to_string(), count));
+        }
+        parts.join(",")
+    }
+
+    pub fn from_save_string(string: &str) -> Result<Goods, &'static str> {
+        let mut goods = Goods::default();
+        for token in string.split(',').filter(|x| !x.is_empty()) {
+            let (name, count) = token.split_once(':').ok_or("Invalid goods")?;
+            let count = count.parse().map_err(|_| "Invalid count")?;
+            match name {
+                "money" => goods.money = Money::new(count),
+                _ => goods.add_item(Item::from_string(name).ok_or("Invalid item")?, count),
+            }
+        }
+        Ok(goods)
+    }
+
+    // What players type in the script mode, like `100 money, 2 diamond`
+    pub fn parse(string: &str) -> Result<Goods, String> {
+        let mut goods = Goods::default();
+        for part in string.split(',').map(str::trim).filter(|x| !x.is_empty()) {
+            let (count, name) = match part.split_once(' ') {
+                Some((count, name)) if count.parse::<u32>().is_ok() => {
+                    (count.parse().unwrap_or(1), name.trim())
+                }
+                _ => (1, part),
+            };
+            if count == 0 {
+                return Err(format!("amount of `{}` must be at least 1", name));
+            }
+            if name.eq_ignore_ascii_case("money") {
+                goods.money += Money::new(count);
+            } else {
+                let item = Item::from_string(name).ok_or(format!("unknown item `{}`", name))?;
+                goods.add_item(item, count);
+            }
+        }
+        Ok(goods)
+    }
+}
+
+pub fn owns(player: &Player, goods: &Goods) -> bool {
+    player.account.money >= goods.money
+        && goods
+            .items
+            .iter()
+            .all(|(item, count)| player.inventory.count_item(*item) >= *count)
+}
+
+// Room for everything coming in, counting the slots freed by what goes out
+pub fn has_room(player: &Player, incoming: &Goods, outgoing: &Goods) -> bool {
+    incoming.items.iter().all(|&(item, _)| {
+        let needed = incoming.pieces(|x| same_bag(x, item));
+        let freed = outgoing.pieces(|x| same_bag(x, item));
+        player.inventory.free_space(item).saturating_add(freed) >= needed
+    })
+}
+
+fn same_bag(a: Item, b: Item) -> bool {
+    matches!(
+        (a, b),
+        (Item::Ore(_), Item::Ore(_))
+            | (Item::Food(_), Item::Food(_))
+            | (Item::Gear(_), Item::Gear(_))
+            | (Item::Consumable(_), Item::Consumable(_))
+    )
+}
+
+// Only called once `owns` said yes, so nothing comes up short
+fn take_out(player: &mut Player, goods: &Goods) {
+    player.account.money = player.account.money.saturating_sub(goods.money);
+    for (item, count) in &goods.items {
+        for _ in 0..*count {
+            player.inventory.remove_item(*item);
+        }
+    }
+}
+
+fn put_in(player: &mut Player, goods: &Goods) {
+    player.account.money += goods.money;
+    for (item, count) in &goods.items {
+        for _ in 0..*count {
+            player.inventory.push_item(*item);
+        }
+    }
+}
+
+pub fn find_player(players: &[Player], username: &str) -> Option<usize> {
+    players
+        .iter()
+        .position(|player| player.account.username == username)
+}
+
+fn pair_mut(players: &mut [Player], a: usize, b: usize) -> (&mut Player, &mut Player) {
+    if a < b {
+        let (left, right) = players.split_at_mut(b);
+        (&mut left[a], &mut right[0])
+    } else {
+        let (left, right) = players.split_at_mut(a);
+        (&mut right[0], &mut left[b])
+    }
+}
+
+pub fn incoming<'a>(trades: &'a [Trade], username: &'a str) -> impl Iterator<Item = &'a Trade> {
+    trades.iter().filter(move |trade| trade.to == username)
+}
+
+pub fn outgoing<'a>(trades: &'a [Trade], username: &'a str) -> impl Iterator<Item = &'a Trade> {
+    trades.iter().filter(move |trade| trade.from == username)
+}
+
+pub fn propose(
+    players: &[Player],
+    trades: &mut Vec<Trade>,
+    from: &str,
+    to: &str,
+    give: Goods,
+    want: Goods,
+    log: &mut Vec<String>,
+) -> bool {
+    if from == to {
+        log.push(tr!("trade.with_yourself"));
+        return false;
+    }
+    let (Some(from_index), Some(_)) = (find_player(players, from), find_player(players, to)) else {
+        log.push(tr!("trade.no_such_account", to));
+        return false;
+    };
+    if give.is_empty() && want.is_empty() {
+        log.push(tr!("trade.empty"));
+        return false;
+    }
+    if !owns(&players[from_index], &give) {
+        log.push(tr!("trade.you_lack", give.describe()));
+        return false;
+    }
+
+    let newest = trades.iter().map(|trade| trade.id).max().unwrap_or(0);
+    let Some(id) = newest.checked_add(1) else {
+        log.push(tr!("trade.no_ids_left"));
+        return false;
+    };
+    log.push(tr!(
+        "trade.offered",
+        id,
+        to,
+        give.describe(),
+        want.describe()
+    ));
+    trades.push(Trade {
+        id,
+        from: from.to_string(),
+        to: to.to_string(),
+        give,
+        want,
+    });
+    true
+}
+
+// Only the account the offer was made to can accept it
+pub fn accept(
+    players: &mut [Player],
+    trades: &mut Vec<Trade>,
+    username: &str,
+    id: u32,
+    log: &mut Vec<String>,
+) -> bool {
+    let Some(position) = trades.iter().position(|x| x.id == id && x.to == username) else {
+        log.push(tr!("trade.no_such_offer", id));
+        return false;
+    };
+    let trade = &trades[position];
+    let (Some(from_index), Some(to_index)) = (
+        find_player(players, &trade.from),
+        find_player(players, &trade.to),
+    ) else {
+        log.push(tr!("trade.no_such_account", trade.from));
+        return false;
+    };
+
+    let (from, to) = pair_mut(players, from_index, to_index);
+    if !owns(from, &trade.give) {
+        log.push(tr!("trade.they_lack", trade.from, trade.give.describe()));
+        return false;
+    }
+    if !owns(to, &trade.want) {
+        log.push(tr!("trade.you_lack", trade.want.describe()));
+        return false;
+    }
+    if !has_room(to, &trade.give, &trade.want) {
+        log.push(tr!("trade.you_have_no_room"));
+        return false;
+    }
+    if !has_room(from, &trade.want, &trade.give) {
+        log.push(tr!("trade.they_have_no_room", trade.from));
+        return false;
+    }
+
+    // both sides checked out, so nothing below can come up short. What goes
+    // out leaves first to free the slots for what comes in.
+    take_out(from, &trade.give);
+    take_out(to, &trade.want);
+    put_in(from, &trade.want);
+    put_in(to, &trade.give);
+
+    let trade = trades.remove(position);
+    log.push(tr!(
+        "trade.done",
+        trade.from,
+        trade.want.describe(),
+        trade.give.describe()
+    ));
+    true
+}
+
+// Declines an offer made to the player, or takes back one they made
+pub fn cancel(trades: &mut Vec<Trade>, username: &str, id: u32, log: &mut Vec<String>) -> bool {
+    let Some(position) = trades
+        .iter()
+        .position(|x| x.id == id && (x.to == username || x.from == username))
+    else {
+        log.push(tr!("trade.no_such_offer", id));
+        return false;
+    };
+
+    let trade = trades.remove(position);
+    match trade.to == username {
+        true => log.push(tr!("trade.declined", trade.id, trade.from)),
+        false => log.push(tr!("trade.withdrawn", trade.id, trade.to)),
+    }
+    true
+}
+
+// One trade per line: `id;from;to;give;want`
+fn parse_trade_string(trade_string: &str) -> Result<Trade, &'static str> {
+    let tokens: Vec<&str> = trade_string.split(';').collect();
+    if tokens.len() != 5 {
+        return Err("Invalid number of tokens");
+    }
+
+    Ok(Trade {
+        id: tokens[0].parse().map_err(|_| "Invalid id")?,
+        from: tokens[1].to_string(),
+        to: tokens[2].to_string(),
+        give: Goods::from_save_string(tokens[3])?,
+        want: Goods::from_save_string(tokens[4])?,
+    })
+}
+
+pub fn parse_trade_file(file_path: &str) -> io::Result<Vec<Trade>> {
+    let file = File::open(file_path)?;
+    let reader = io::BufReader::new(file);
+    let mut trades = Vec::new();
+
+    for line_content in reader.lines().map_while(Result::ok) {
+        if let Ok(trade) = parse_trade_string(&line_content) {
+            trades.push(trade);
+        }
+    }
+
+    Ok(trades)
+}
+
+pub fn update_trade_file(trades: &[Trade], file_path: &str) -> io::Result<()> {
+    let mut file = File::create(file_path)?;
+
+    for trade in trades {
+        let trade_string = format!(
+            "{};{};{};{};{}\n",
+            trade.id,
+            trade.from,
+            trade.to,
+            trade.give.to_save_string(),
+            trade.want.to_save_string()
+        );
+        file.write_all(trade_string.as_bytes())?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::account::Account;
+    use crate::inventory::{Inventory, Ore};
+
+    fn player(username: &str) -> Player {
+        Player::new(
+            Inventory::create_empty(),
+            Account::new_account(username.to_string()),
+        )
+    }
+
+    // alice offers $100 to bob for 2 coal
+    fn offer(players: &mut [Player]) -> Vec<Trade> {
+        let mut trades = Vec::new();
+        let give = Goods::parse("100 money").unwrap();
+        let want = Goods::parse("2 coal").unwrap();
+        assert!(propose(
+            players,
+            &mut trades,
+            "alice",
+            "bob",
+            give,
+            want,
+            &mut Vec::new()
+        ));
+        trades
+    }
+
+    fn coal(player: &Player) -> u32 {
+        player.inventory.count_ore(Ore::Coal)
+    }
+
+    #[test]
+    fn accept_moves_nothing_when_the_offer_is_no_longer_covered() {
+        let mut players = vec![player("alice"), player("bob")];
+        let mut trades = offer(&mut players);
+        players[1].inventory.push_item(Item::Ore(Ore::Coal));
+        players[1].inventory.push_item(Item::Ore(Ore::Coal));
+        // spent between offering and the accept
+        players[0].account.money = Money::new(99);
+
+        let id = trades[0].id;
+        assert!(!accept(
+            &mut players,
+            &mut trades,
+            "bob",
+            id,
+            &mut Vec::new()
+        ));
+        assert_eq!(players[0].account.money, Money::new(99));
+        assert_eq!(players[1].account.money, Money::new(100));
+        assert_eq!(coal(&players[0]), 0);
+        assert_eq!(coal(&players[1]), 2);
+        assert_eq!(trades.len(), 1);
+    }
+
+    #[test]
+    fn accept_moves_nothing_when_the_accepting_side_falls_short() {
+        let mut players = vec![player("alice"), player("bob")];
+        let mut trades = offer(&mut players);
+        players[1].inventory.push_item(Item::Ore(Ore::Coal));
+
+        let id = trades[0].id;
+        assert!(!accept(
+            &mut players,
+            &mut trades,
+            "bob",
+            id,
+            &mut Vec::new()
+        ));
+        assert_eq!(players[0].account.money, Money::new(100));
+        assert_eq!(players[1].account.money, Money::new(100));
+        assert_eq!(coal(&players[0]), 0);
+        assert_eq!(coal(&players[1]), 1);
+        assert_eq!(trades.len(), 1);
+    }
+
+    #[test]
+    fn accept_moves_both_sides_at_once() {
+        let mut players = vec![player("alice"), player("bob")];
+        let mut trades = offer(&mut players);
+        players[1].inventory.push_item(Item::Ore(Ore::Coal));
+        players[1].inventory.push_item(Item::Ore(Ore::Coal));
+
+        let id = trades[0].id;
+        assert!(accept(
+            &mut players,
+            &mut trades,
+            "bob",
+            id,
+            &mut Vec::new()
+        ));
+        assert_eq!(players[0].account.money, Money::ZERO);
+        assert_eq!(players[1].account.money, Money::new(200));
+        assert_eq!(coal(&players[0]), 2);
+        assert_eq!(coal(&players[1]), 0);
+        assert!(trades.is_empty());
+    }
+}
@@ -0,0 +1,501 @@
+// Full-screen frontend, only built with the `tui` feature.
+// Everything the player does goes through the same game actions as the
+// line based menus, this only decides how it looks.
+use crate::auction;
+use crate::clock::Clock;
+use crate::consumable::{Consumable, ALL_CONSUMABLES};
+use crate::game;
+use crate::inventory::{Food, Item, ALL_ORES};
+use crate::lang::{text, tr};
+use crate::mine::{Direction, MAP_LEGEND};
+use crate::player::{Player, MAX_HUNGER};
+use crate::world::World;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Direction as LayoutDirection, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, Gauge, List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use std::io;
+
+// How many log lines are kept around
+const LOG_LIMIT: usize = 50;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Screen {
+    Accounts,
+    Surface,
+    Mine,
+}
+
+#[derive(Clone, Copy)]
+enum Action {
+    Login(usize),
+    GoMining,
+    SellAll,
+    BuyFood(Food),
+    UpgradePickaxe,
+    Wait,
+    Cook,
+    Logout,
+    Move(Direction),
+    Eat,
+    Use(Consumable),
+    Return,
+    Quit,
+}
+
+// Accounts past the ninth have no hotkey, only the arrow keys reach them
+struct MenuItem {
+    hotkey: Option<char>,
+    label: String,
+    action: Action,
+}
+
+struct App {
+    screen: Screen,
+    player: Option<usize>,
+    menu_state: ListState,
+    log: Vec<String>,
+    quit: bool,
+}
+
+pub fn run(world: &mut World) -> io::Result<()> {
+    let mut terminal = ratatui::init();
+    let result = event_loop(&mut terminal, world);
+    ratatui::restore();
+    result
+}
+
+fn event_loop(terminal: &mut DefaultTerminal, world: &mut World) -> io::Result<()> {
+    let mut app = App {
+        screen: Screen::Accounts,
+        player: None,
+        menu_state: ListState::default().with_selected(Some(0)),
+        log: vec![tr!("main.welcome")],
+        quit: false,
+    };
+
+    while !app.quit {
+        let menu = menu_items(&app, &world.players);
+        let clock = &world.info.clock;
+        terminal.draw(|frame| draw(frame, &mut app, &world.players, clock, &menu))?;
+
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+
+        let selected = app.menu_state.selected().unwrap_or(0);
+        let action = match key.code {
+            KeyCode::Up => {
+                app.menu_state
+                    .select(Some(selected.checked_sub(1).unwrap_or(menu.len() - 1)));
+                None
+            }
+            KeyCode::Down => {
+                app.menu_state.select(Some((selected + 1) % menu.len()));
+                None
+            }
+            KeyCode::Enter => menu.get(selected).map(|item| item.action),
+            KeyCode::Esc => Some(back_action(app.screen)),
+            // raw mode hands Ctrl-C over as a key, which would otherwise hit `c`
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                Some(Action::Quit)
+            }
+            KeyCode::Char(c) => menu
+                .iter()
+                .find(|item| item.hotkey == Some(c.to_ascii_lowercase()))
+                .map(|item| item.action),
+            _ => None,
+        };
+
+        if let Some(action) = action {
+            perform(&mut app, world, action);
+        }
+    }
+
+    Ok(())
+}
+
+fn back_action(screen: Screen) -> Action {
+    match screen {
+        Screen::Accounts => Action::Quit,
+        Screen::Surface => Action::Logout,
+        Screen::Mine => Action::Return,
+    }
+}
+
+fn menu_items(app: &App, players: &[Player]) -> Vec<MenuItem> {
+    let item = |hotkey, label: &str, action| MenuItem {
+        hotkey: Some(hotkey),
+        label: label.to_string(),
+        action,
+    };
+
+    match app.screen {
+        Screen::Accounts => {
+            let mut items: Vec<MenuItem> = players
+                .iter()
+                .enumerate()
+                .map(|(i, player)| MenuItem {
+                    hotkey: char::from_digit(i as u32 + 1, 10),
+                    label: tr!(
+                        "login.account",
+                        player.account.username,
+                        player.account.money
+                    ),
+                    action: Action::Login(i),
+                })
+                .collect();
+            items.push(item('q', text("menu.exit"), Action::Quit));
+            items
+        }
+        Screen::Surface => {
+            let mut items = vec![
+                item('m', text("play.mining"), Action::GoMining),
+                item('s', text("tui.sell_all"), Action::SellAll),
+            ];
+            for (hotkey, food) in [('1', Food::Apple), ('2', Food::Chicken), ('3', Food::Beef)] {
+                items.push(MenuItem {
+                    hotkey: Some(hotkey),
+                    label: tr!("shop.buy_food", food.name(), food.price()),
+                    action: Action::BuyFood(food),
+                });
+            }
+            if let Some(player) = app.player.and_then(|i| players.get(i)) {
+                if !player.is_pickaxe_maxed() {
+                    items.push(MenuItem {
+                        hotkey: Some('u'),
+                        label: tr!("shop.upgrade_pickaxe_cost", player.upgrade_pickaxe_cost()),
+                        action: Action::UpgradePickaxe,
+                    });
+                }
+            }
+            items.push(item('c', text("play.cook"), Action::Cook));
+            items.push(item('z', text("play.wait"), Action::Wait));
+            items.push(item('q', text("tui.log_out"), Action::Logout));
+            items
+        }
+        Screen::Mine => {
+            let mut items = vec![
+                item('w', text("mining.north"), Action::Move(Direction::North)),
+                item('s', text("mining.south"), Action::Move(Direction::South)),
+                item('d', text("mining.east"), Action::Move(Direction::East)),
+                item('a', text("mining.west"), Action::Move(Direction::West)),
+                item('x', text("mining.down"), Action::Move(Direction::Down)),
+                item('e', text("mining.eat"), Action::Eat),
+            ];
+            // only what the player has on them, numbered by kind
+            if let Some(player) = app.player.and_then(|i| players.get(i)) {
+                for (hotkey, consumable) in ('1'..).zip(ALL_CONSUMABLES) {
+                    let count = player.inventory.count_item(Item::Consumable(consumable));
+                    if count > 0 {
+                        let label = tr!("trade.owned", consumable.name(), count);
+                        items.push(MenuItem {
+                            hotkey: Some(hotkey),
+                            label,
+                            action: Action::Use(consumable),
+                        });
+                    }
+                }
+            }
+            items.push(item('r', text("menu.return"), Action::Return));
+            items
+        }
+    }
+}
+
+fn perform(app: &mut App, world: &mut World, action: Action) {
+    let players = &mut world.players;
+    let mut log = Vec::new();
+
+    match action {
+        Action::Quit => app.quit = true,
+        Action::Login(index) => {
+            let player = &players[index];
+            app.player = Some(index);
+            log.push(tr!("main.welcome_player", player.account.username));
+            // a trip that was cut short carries on
+            app.screen = match player.is_in_mine() {
+                true => {
+                    log.push(tr!("game.still_in_mine", player.get_depth()));
+                    Screen::Mine
+                }
+                false => Screen::Surface,
+            };
+        }
+        Action::Logout => {
+            app.player = None;
+            app.screen = Screen::Accounts;
+        }
+        Action::GoMining => {
+            app.screen = Screen::Mine;
+            log.push(tr!("tui.enter_mine"));
+        }
+        Action::Return => {
+            app.screen = Screen::Surface;
+            log.push(tr!("tui.leave_mine"));
+        }
+        _ => (),
+    }
+
+    let clock = &mut world.info.clock;
+    // digging is a turn for the auction house
+    let mut dug = false;
+    if let Some(player) = app.player.and_then(|i| players.get_mut(i)) {
+        match action {
+            Action::GoMining => game::enter_mine(player, clock, &mut log),
+            Action::Return => game::leave_mine(player, clock, &mut log),
+            Action::Wait => {
+                game::wait_for_shop(player, clock, &mut log);
+            }
+            Action::SellAll | Action::BuyFood(_) | Action::UpgradePickaxe
+                if !clock.is_shop_open() =>
+            {
+                game::shop_is_open(clock, &mut log);
+            }
+            Action::SellAll => {
+                game::sell_all_ores(player, &mut world.market, &mut log);
+            }
+            Action::BuyFood(food) => {
+                game::buy_food(player, food, &mut log);
+            }
+            Action::UpgradePickaxe => {
+                game::upgrade_pickaxe(player, &mut log);
+            }
+            Action::Move(direction) => dug = game::dig(player, clock, direction, &mut log),
+            Action::Cook => {
+                let foods = &player.inventory.foods;
+                match foods
+                    .iter()
+                    .position(|x| x.is_some_and(|food| food.cooked().is_some()))
+                {
+                    Some(index) => {
+                        game::cook(player, clock, index as i32 + 1, &mut log);
+                    }
+                    None => log.push(tr!("tui.nothing_to_cook")),
+                }
+            }
+            Action::Use(consumable) => {
+                let done = game::use_consumable(player, clock, consumable, &mut log);
+                if done && consumable == Consumable::Rope {
+                    app.screen = Screen::Surface;
+                }
+            }
+            Action::Eat => match player.inventory.foods.iter().position(|x| x.is_some()) {
+                Some(index) => {
+                    game::eat(player, index as i32 + 1, &mut log);
+                }
+                None => log.push(tr!("tui.no_food")),
+            },
+            _ => (),
+        }
+    }
+
+    if let Some(index) = app.player.filter(|_| dug) {
+        let username = world.players[index].account.username.clone();
+        auction::advance(&mut world.players, &mut world.auctions, &username, &mut log);
+    }
+
+    if !matches!(action, Action::Move(_) | Action::Eat) {
+        app.menu_state.select(Some(0));
+    }
+    app.log.extend(log);
+    let overflow = app.log.len().saturating_sub(LOG_LIMIT);
+    app.log.drain(..overflow);
+}
+
+fn draw(frame: &mut Frame, app: &mut App, players: &[Player], clock: &Clock, menu: &[MenuItem]) {
+    let rows = Layout::default()
+        .direction(LayoutDirection::Vertical)
+        .constraints([Constraint::Min(10), Constraint::Length(8)])
+        .split(frame.area());
+    let columns = Layout::default()
+        .direction(LayoutDirection::Horizontal)
+        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+        .split(rows[0]);
+    let side = Layout::default()
+        .direction(LayoutDirection::Vertical)
+        .constraints([
+            Constraint::Length(6),
+            Constraint::Min(4),
+            Constraint::Length(menu.len() as u16 + 2),
+        ])
+        .split(columns[1]);
+
+    let player = app.player.and_then(|i| players.get(i));
+    match (app.screen, player) {
+        (Screen::Mine, Some(player)) => draw_map(frame, columns[0], player),
+        (_, Some(player)) => draw_surface(frame, columns[0], player),
+        (_, None) => {
+            let title = Paragraph::new(text("tui.choose_account")).block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(text("tui.title")),
+            );
+            frame.render_widget(title, columns[0]);
+        }
+    }
+
+    if let Some(player) = player {
+        draw_stats(frame, side[0], player, clock);
+        draw_inventory(frame, side[1], player);
+    }
+    draw_menu(frame, side[2], app, menu);
+    draw_log(frame, rows[1], &app.log);
+}
+
+fn draw_map(frame: &mut Frame, area: Rect, player: &Player) {
+    // fit as much of the map as the panel allows, leaving room for the legend
+    let radius = ((area.height as i32 - 3) / 2).clamp(1, 10);
+    let mut lines: Vec<Line> = Vec::new();
+    let map = player.mine.render(radius);
+    for i in 0..map.len().max(MAP_LEGEND.len()) {
+        let row = map
+            .get(i)
+            .cloned()
+            .unwrap_or_else(|| " ".repeat(map[0].len()));
+        let legend = match MAP_LEGEND.get(i) {
+            Some((glyph, name)) => format!("   '{}' {}", glyph, text(name)),
+            None => String::new(),
+        };
+        lines.push(Line::from(format!("{}{}", row, legend)));
+    }
+
+    let (x, y, depth) = player.mine.position();
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(tr!("tui.mine_title", x, y, depth));
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+fn draw_surface(frame: &mut Frame, area: Rect, player: &Player) {
+    let mut lines = vec![
+        Line::from(tr!("main.welcome_player", player.account.username)),
+        Line::from(tr!("tui.pickaxe_level", player.account.pickaxe_level)),
+        Line::from(""),
+        Line::from(text("quest.active")),
+    ];
+    if player.quests.active.is_empty() {
+        lines.push(Line::from(format!("- {}", text("common.none"))));
+    }
+    for quest in &player.quests.active {
+        lines.push(Line::from(format!("- {}", quest.describe())));
+    }
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(text("tui.surface"));
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+fn draw_stats(frame: &mut Frame, area: Rect, player: &Player, clock: &Clock) {
+    let block = Block::default().borders(Borders::ALL).title(format!(
+        "{} - {}",
+        text("tui.status"),
+        clock.describe()
+    ));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let lines = Layout::default()
+        .direction(LayoutDirection::Vertical)
+        .constraints([
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .split(inner);
+
+    let health = Gauge::default()
+        .gauge_style(Style::default().fg(Color::Red))
+        .label(tr!("tui.health", player.get_health()))
+        .percent(player.get_health().min(100) as u16);
+    frame.render_widget(health, lines[0]);
+
+    let hunger = Gauge::default()
+        .gauge_style(Style::default().fg(Color::Yellow))
+        .label(tr!("mining.hunger", player.get_hunger(), MAX_HUNGER))
+        .percent(player.get_hunger() as u16 * 100 / MAX_HUNGER as u16);
+    frame.render_widget(hunger, lines[1]);
+
+    frame.render_widget(
+        Paragraph::new(tr!(
+            "tui.depth_money",
+            player.get_depth(),
+            player.account.money
+        )),
+        lines[2],
+    );
+    frame.render_widget(
+        Paragraph::new(tr!("mining.effects", player.effects.describe())),
+        lines[3],
+    );
+}
+
+fn draw_inventory(frame: &mut Frame, area: Rect, player: &Player) {
+    let mut lines = Vec::new();
+    for ore in ALL_ORES {
+        lines.push(Line::from(format!(
+            "{}: {}",
+            ore.name(),
+            player.inventory.count_ore(ore)
+        )));
+    }
+    let foods: Vec<&str> = player
+        .inventory
+        .foods
+        .iter()
+        .flatten()
+        .map(|food| food.name())
+        .collect();
+    lines.push(Line::from(tr!(
+        "tui.food",
+        if foods.is_empty() {
+            text("common.none").to_string()
+        } else {
+            foods.join(", ")
+        }
+    )));
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(text("tui.inventory"));
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+fn draw_menu(frame: &mut Frame, area: Rect, app: &mut App, menu: &[MenuItem]) {
+    let items: Vec<ListItem> = menu
+        .iter()
+        .map(|item| match item.hotkey {
+            Some(hotkey) => ListItem::new(format!("[{}] {}", hotkey, item.label)),
+            None => ListItem::new(format!("    {}", item.label)),
+        })
+        .collect();
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(text("tui.menu")),
+        )
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .highlight_symbol("> ");
+    frame.render_stateful_widget(list, area, &mut app.menu_state);
+}
+
+fn draw_log(frame: &mut Frame, area: Rect, log: &[String]) {
+    let visible = area.height.saturating_sub(2) as usize;
+    let lines: Vec<Line> = log
+        .iter()
+        .skip(log.len().saturating_sub(visible))
+        .map(|message| Line::from(message.as_str()))
+        .collect();
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(text("tui.log"));
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
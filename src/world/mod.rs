@@ -0,0 +1,307 @@
+// Every world is a folder under the worlds directory holding its own save
+// files, market and a world.txt with
+// `seed,created,last_played,version,clock`.
+use crate::account::Account;
+use crate::auction::{parse_auction_file, update_auction_file, AuctionHouse};
+use crate::clock::Clock;
+use crate::inventory::Inventory;
+use crate::lang::text;
+use crate::market::{load_market_file, update_market_file, Market};
+use crate::mine::MineMap;
+use crate::player::Player;
+use crate::save::{load_players, save_players, SAVE_DIR};
+use crate::timestamp::now;
+use crate::trade::{parse_trade_file, update_trade_file, Trade};
+use std::fs::{self, File};
+use std::io::{self, Write};
+
+pub const WORLDS_DIR: &str = "src/worlds";
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+// Where saves made before there were worlds get imported to
+pub const DEFAULT_WORLD: &str = "default";
+
+const SAVE_FILES: [&str; 7] = [
+    "account.txt",
+    "inventory.txt",
+    "achievement.txt",
+    "quest.txt",
+    "player.txt",
+    "equipment.txt",
+    "mine.txt",
+];
+
+pub struct WorldInfo {
+    pub name: String,
+    pub seed: u64,
+    // unix timestamps
+    pub created: u64,
+    pub last_played: u64,
+    // game version that last saved the world
+    pub version: String,
+    pub clock: Clock,
+}
+
+// Why an account can't get the username it asked for
+#[derive(Clone, Copy)]
+pub enum NameError {
+    Empty,
+    NotAlphanumeric,
+    Taken,
+}
+
+impl NameError {
+    // What the player is told
+    pub fn message(self) -> &'static str {
+        text(match self {
+            NameError::Empty => "register.empty",
+            NameError::NotAlphanumeric => "register.not_alphanumeric",
+            NameError::Taken => "register.taken",
+        })
+    }
+
+    // The script mode's reason, which stays in English
+    pub fn reason(self) -> &'static str {
+        match self {
+            NameError::Empty => "missing username",
+            NameError::NotAlphanumeric => "username must be alphanumeric",
+            NameError::Taken => "account already exists",
+        }
+    }
+}
+
+pub struct World {
+    pub info: WorldInfo,
+    pub players: Vec<Player>,
+    pub market: Market,
+    // offers waiting for an answer
+    pub trades: Vec<Trade>,
+    pub auctions: AuctionHouse,
+    dir: String,
+}
+
+impl World {
+    // New accounts get a mine generated from the world seed, so copies of a
+    // world grow the same mines for the same usernames
+    pub fn add_player(&mut self, username: String) -> Result<&mut Player, NameError> {
+        if username.is_empty() {
+            return Err(NameError::Empty);
+        }
+        if !username.chars().all(|c| c.is_alphanumeric()) {
+            return Err(NameError::NotAlphanumeric);
+        }
+        // every save file finds accounts by name
+        if self.players.iter().any(|x| x.account.username == username) {
+            return Err(NameError::Taken);
+        }
+
+        let mut player = Player::new(Inventory::create_empty(), Account::new_account(username));
+        player.mine = MineMap::new(player_seed(self.info.seed, &player.account.username));
+        self.players.push(player);
+        Ok(self.players.last_mut().unwrap())
+    }
+}
+
+fn player_seed(world_seed: u64, username: &str) -> u64 {
+    // FNV-1a
+    let mut hash = 0xcbf2_9ce4_8422_2325 ^ world_seed;
+    for byte in username.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
+fn world_dir(root: &str, name: &str) -> String {
+    format!("{}/{}", root, name)
+}
+
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+}
+
+fn parse_world_string(name: &str, world_string: &str) -> Result<WorldInfo, &'static str> {
+    let tokens: Vec<&str> = world_string.trim().split(',').collect();
+    // worlds saved before there was a clock have no time yet
+    if tokens.len() != 4 && tokens.len() != 5 {
+        return Err("Invalid number of tokens");
+    }
+    let clock = match tokens.get(4) {
+        Some(minutes) => Clock {
+            minutes: minutes.parse().map_err(|_| "Invalid clock")?,
+        },
+        None => Clock::default(),
+    };
+
+    Ok(WorldInfo {
+        name: name.to_string(),
+        seed: tokens[0].parse().map_err(|_| "Invalid seed")?,
+        created: tokens[1].parse().unwrap_or(0),
+        last_played: tokens[2].parse().unwrap_or(0),
+        version: tokens[3].to_string(),
+        clock,
+    })
+}
+
+fn load_world_info(root: &str, name: &str) -> io::Result<WorldInfo> {
+    let path = format!("{}/world.txt", world_dir(root, name));
+    let content = fs::read_to_string(path)?;
+    parse_world_string(name, &content).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn update_world_file(info: &WorldInfo, dir: &str) -> io::Result<()> {
+    let mut file = File::create(format!("{}/world.txt", dir))?;
+    let world_string = format!(
+        "{},{},{},{},{}\n",
+        info.seed, info.created, info.last_played, info.version, info.clock.minutes
+    );
+    file.write_all(world_string.as_bytes())
+}
+
+// Most recently played first
+pub fn list_worlds(root: &str) -> io::Result<Vec<WorldInfo>> {
+    let mut worlds = Vec::new();
+    let Ok(entries) = fs::read_dir(root) else {
+        return Ok(worlds);
+    };
+
+    for entry in entries.map_while(Result::ok) {
+        let name = entry.file_name().to_string_lossy().to_string();
+        // folders without a world.txt aren't worlds
+        if let Ok(info) = load_world_info(root, &name) {
+            worlds.push(info);
+        }
+    }
+
+    worlds.sort_by_key(|info| std::cmp::Reverse(info.last_played));
+    Ok(worlds)
+}
+
+pub fn create_world(root: &str, name: &str) -> io::Result<WorldInfo> {
+    if !is_valid_name(name) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Invalid world name",
+        ));
+    }
+
+    let dir = world_dir(root, name);
+    fs::create_dir_all(root)?;
+    // fails if the world already exists
+    fs::create_dir(&dir)?;
+
+    let info = WorldInfo {
+        name: name.to_string(),
+        seed: rand::random(),
+        created: now(),
+        last_played: 0,
+        version: VERSION.to_string(),
+        clock: Clock::default(),
+    };
+    update_world_file(&info, &dir)?;
+    update_market_file(&Market::new(info.seed), &format!("{}/market.txt", dir))?;
+    save_players(&[], &dir)?;
+    Ok(info)
+}
+
+pub fn copy_world(root: &str, from: &str, to: &str) -> io::Result<()> {
+    let mut info = load_world_info(root, from)?;
+    if !is_valid_name(to) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Invalid world name",
+        ));
+    }
+
+    let dir = world_dir(root, to);
+    fs::create_dir(&dir)?;
+    for entry in fs::read_dir(world_dir(root, from))?.map_while(Result::ok) {
+        if entry.path().is_file() {
+            fs::copy(
+                entry.path(),
+                format!("{}/{}", dir, entry.file_name().to_string_lossy()),
+            )?;
+        }
+    }
+
+    // the copy keeps the seed, but it is a new world
+    info.name = to.to_string();
+    info.created = now();
+    info.last_played = 0;
+    update_world_file(&info, &dir)
+}
+
+pub fn delete_world(root: &str, name: &str) -> io::Result<()> {
+    // make sure it really is a world before removing anything
+    load_world_info(root, name)?;
+    fs::remove_dir_all(world_dir(root, name))
+}
+
+pub fn open_world(root: &str, name: &str) -> io::Result<World> {
+    let mut info = load_world_info(root, name)?;
+    let dir = world_dir(root, name);
+    let players = load_players(&dir)?;
+
+    let market_path = format!("{}/market.txt", dir);
+    let mut market = load_market_file(&market_path, info.seed).unwrap_or(Market::new(info.seed));
+    market.recover();
+    let trades = parse_trade_file(&format!("{}/trades.txt", dir)).unwrap_or_default();
+    let auctions = parse_auction_file(&format!("{}/auctions.txt", dir)).unwrap_or_default();
+
+    info.last_played = now();
+    update_world_file(&info, &dir)?;
+
+    Ok(World {
+        info,
+        players,
+        market,
+        trades,
+        auctions,
+        dir,
+    })
+}
+
+pub fn save_world(world: &mut World) -> io::Result<()> {
+    world.info.version = VERSION.to_string();
+    update_world_file(&world.info, &world.dir)?;
+    update_market_file(&world.market, &format!("{}/market.txt", world.dir))?;
+    update_trade_file(&world.trades, &format!("{}/trades.txt", world.dir))?;
+    update_auction_file(&world.auctions, &format!("{}/auctions.txt", world.dir))?;
+    save_players(&world.players, &world.dir)
+}
+
+// Saves from before worlds existed become the default world, the old
+// files are left where they are
+pub fn import_legacy_save(root: &str) -> io::Result<()> {
+    let legacy_accounts = format!("{}/account.txt", SAVE_DIR);
+    if !list_worlds(root)?.is_empty() || !fs::metadata(&legacy_accounts).is_ok_and(|x| x.is_file())
+    {
+        return Ok(());
+    }
+
+    let info = create_world(root, DEFAULT_WORLD)?;
+    let dir = world_dir(root, &info.name);
+    for file_name in SAVE_FILES {
+        let legacy_path = format!("{}/{}", SAVE_DIR, file_name);
+        if fs::metadata(&legacy_path).is_ok() {
+            fs::copy(legacy_path, format!("{}/{}", dir, file_name))?;
+        }
+    }
+    Ok(())
+}
+
+// Picks the world named on the command line, or the last played one,
+// creating the default world when there is none
+pub fn open_world_or_default(root: &str, name: Option<&str>) -> io::Result<World> {
+    if let Some(name) = name {
+        return open_world(root, name);
+    }
+
+    let name = match list_worlds(root)?.first() {
+        Some(info) => info.name.clone(),
+        None => create_world(root, DEFAULT_WORLD)?.name,
+    };
+    open_world(root, &name)
+}